@@ -0,0 +1,241 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::instruction::PrivacyInstruction;
+use crate::note::NotePlaintext;
+
+// Transaction-construction surface for wallet code, mirroring the `builder`
+// module of Zcash's Orchard (`Builder::add_spend`, `Builder::add_output`,
+// `SpendInfo`). The raw `shield`/`withdraw` calls operate one note at a time;
+// a [`TransferBuilder`] accumulates several spends and outputs, checks that the
+// transfer balances, and lowers the whole thing to the instruction stream plus
+// the public-input vectors the verifier consumes.
+
+/// A spendable note together with the authentication data needed to prove its
+/// membership in the tree, modelled on Orchard's `SpendInfo`.
+#[derive(Debug, Clone)]
+pub struct SpendInfo {
+    /// The note being spent.
+    pub note: NotePlaintext,
+    /// Sibling nodes on the path from the note's leaf to the root.
+    pub path: Vec<[u8; 32]>,
+    /// Bit-packed left/right selectors for each level of `path`.
+    pub indices: Vec<u8>,
+    /// Nullifier derived from the note, published to prevent double-spends.
+    pub nullifier: [u8; 32],
+}
+
+/// A new shielded output: the commitment recorded in the tree and its value.
+#[derive(Debug, Clone)]
+pub struct OutputInfo {
+    /// Commitment inserted as a fresh leaf.
+    pub commitment: [u8; 32],
+    /// Value of the output note.
+    pub amount: u64,
+}
+
+/// Errors returned by [`TransferBuilder::add_spend`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum SpendError {
+    /// The note carries no spendable value and cannot be identified.
+    UnknownNote,
+    /// The Merkle path is missing or does not line up with its index bits.
+    StalePath,
+    /// The accumulated input value would overflow a `u64`.
+    ValueOverflow,
+}
+
+impl fmt::Display for SpendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpendError::UnknownNote => write!(f, "spend refers to an unknown note"),
+            SpendError::StalePath => write!(f, "stale or missing Merkle path for spend"),
+            SpendError::ValueOverflow => write!(f, "input value overflow"),
+        }
+    }
+}
+
+impl Error for SpendError {}
+
+/// Errors returned by [`TransferBuilder::add_output`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum OutputError {
+    /// The commitment is the all-zero sentinel and cannot be inserted.
+    InvalidCommitment,
+    /// The accumulated output value would overflow a `u64`.
+    ValueOverflow,
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputError::InvalidCommitment => write!(f, "output carries an invalid commitment"),
+            OutputError::ValueOverflow => write!(f, "output value overflow"),
+        }
+    }
+}
+
+impl Error for OutputError {}
+
+/// Raised by [`TransferBuilder::build`] when inputs and outputs do not balance.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ValueBalanceError {
+    /// Total value of the spent notes.
+    pub inputs: u64,
+    /// Total value of the new outputs plus the public withdraw amount.
+    pub outputs: u64,
+}
+
+impl fmt::Display for ValueBalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value imbalance: {} in, {} out",
+            self.inputs, self.outputs
+        )
+    }
+}
+
+impl Error for ValueBalanceError {}
+
+/// A balanced transfer lowered to its on-chain form.
+#[derive(Debug, Clone)]
+pub struct TransferPlan {
+    /// Instructions to submit, outputs first then spends.
+    pub instructions: Vec<PrivacyInstruction>,
+    /// One public-input vector per spend, laid out as the verifier expects:
+    /// `root || nullifier_hash || recipient || amount || fee` (each a 32-byte
+    /// big-endian field element).
+    pub public_inputs: Vec<Vec<u8>>,
+}
+
+/// Accumulates the spends and outputs of a single shielded transfer.
+pub struct TransferBuilder {
+    root: [u8; 32],
+    recipient: [u8; 32],
+    spends: Vec<SpendInfo>,
+    outputs: Vec<OutputInfo>,
+    input_total: u64,
+    output_total: u64,
+}
+
+impl TransferBuilder {
+    /// Start a transfer proving against `root` and paying the public withdraw
+    /// amount to `recipient`.
+    pub fn new(root: [u8; 32], recipient: [u8; 32]) -> Self {
+        Self {
+            root,
+            recipient,
+            spends: Vec::new(),
+            outputs: Vec::new(),
+            input_total: 0,
+            output_total: 0,
+        }
+    }
+
+    /// Add a note to spend, checking that it is identifiable and that its
+    /// authentication path is well-formed.
+    pub fn add_spend(
+        &mut self,
+        note: NotePlaintext,
+        path: Vec<[u8; 32]>,
+        indices: Vec<u8>,
+        nullifier: [u8; 32],
+    ) -> Result<(), SpendError> {
+        if note.amount == 0 || nullifier == [0u8; 32] {
+            return Err(SpendError::UnknownNote);
+        }
+        if path.is_empty() || indices.len() * 8 < path.len() {
+            return Err(SpendError::StalePath);
+        }
+
+        self.input_total = self
+            .input_total
+            .checked_add(note.amount)
+            .ok_or(SpendError::ValueOverflow)?;
+
+        self.spends.push(SpendInfo {
+            note,
+            path,
+            indices,
+            nullifier,
+        });
+        Ok(())
+    }
+
+    /// Add a fresh output commitment of the given value.
+    pub fn add_output(&mut self, commitment: [u8; 32], amount: u64) -> Result<(), OutputError> {
+        if commitment == [0u8; 32] {
+            return Err(OutputError::InvalidCommitment);
+        }
+
+        self.output_total = self
+            .output_total
+            .checked_add(amount)
+            .ok_or(OutputError::ValueOverflow)?;
+
+        self.outputs.push(OutputInfo { commitment, amount });
+        Ok(())
+    }
+
+    /// Validate value balance and lower the transfer to its instruction stream
+    /// and per-spend public inputs. `public_amount` is the value leaving the
+    /// pool to `recipient`; inputs must equal outputs plus this amount.
+    pub fn build(self, public_amount: u64) -> Result<TransferPlan, ValueBalanceError> {
+        let spent = self
+            .output_total
+            .checked_add(public_amount)
+            .ok_or(ValueBalanceError {
+                inputs: self.input_total,
+                outputs: u64::MAX,
+            })?;
+
+        if self.input_total != spent {
+            return Err(ValueBalanceError {
+                inputs: self.input_total,
+                outputs: spent,
+            });
+        }
+
+        let mut instructions = Vec::with_capacity(self.outputs.len() + self.spends.len());
+        for output in &self.outputs {
+            instructions.push(PrivacyInstruction::Shield {
+                amount: output.amount,
+                commitment: output.commitment,
+                encrypted_note: Vec::new(),
+            });
+        }
+
+        let mut public_inputs = Vec::with_capacity(self.spends.len());
+        for spend in &self.spends {
+            instructions.push(PrivacyInstruction::Withdraw {
+                amount: spend.note.amount,
+                fee: 0,
+                root: self.root,
+                nullifier_hash: spend.nullifier,
+                recipient: self.recipient,
+                proof: Vec::new(),
+            });
+
+            let mut inputs = Vec::with_capacity(5 * 32);
+            inputs.extend_from_slice(&self.root);
+            inputs.extend_from_slice(&spend.nullifier);
+            inputs.extend_from_slice(&self.recipient);
+            inputs.extend_from_slice(&amount_to_field(spend.note.amount));
+            inputs.extend_from_slice(&amount_to_field(0));
+            public_inputs.push(inputs);
+        }
+
+        Ok(TransferPlan {
+            instructions,
+            public_inputs,
+        })
+    }
+}
+
+/// Encode a `u64` value as a 32-byte big-endian field element.
+fn amount_to_field(amount: u64) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[24..].copy_from_slice(&amount.to_be_bytes());
+    out
+}
@@ -10,6 +10,9 @@ pub enum PrivacyError {
     NullifierAlreadyUsed,
     InvalidRecipient,
     InsufficientFunds,
+    InvalidMint,
+    EpochOutOfRange,
+    RateLimitExceeded,
 }
 
 impl From<PrivacyError> for ProgramError {
@@ -4,18 +4,67 @@ use borsh::{BorshDeserialize, BorshSerialize};
 pub enum PrivacyInstruction {
     Initialize {
         merkle_tree_height: u8,
+        /// SPL mint to back the pool, or `None` for a native-SOL pool.
+        mint: Option<[u8; 32]>,
     },
     
     Shield {
         amount: u64,
         commitment: [u8; 32],
+        /// Opaque AEAD ciphertext of the note, emitted on-chain so the
+        /// recipient can trial-decrypt it and recover the opening. Empty when
+        /// the opening is distributed out of band.
+        encrypted_note: Vec<u8>,
     },
     
+    /// Shield several commitments in a single transaction. `amounts[i]` backs
+    /// `commitments[i]`; the two vectors must be the same length. Lets a relayer
+    /// aggregate deposits and append them with one batched tree update.
+    ShieldBatch {
+        amounts: Vec<u64>,
+        commitments: Vec<[u8; 32]>,
+    },
+
+    /// Withdraw against a proof of membership in one of the recent roots.
+    ///
+    /// The prover must supply the authentication path for its commitment when
+    /// building `proof` off-chain. Note that [`crate::state::MerkleTree::authentication_path`]
+    /// only serves the most-recently-inserted (frontier) leaf; a prover
+    /// withdrawing an older commitment must track its path client-side with
+    /// [`crate::state::IncrementalWitness`] from the moment of the deposit, or
+    /// reconstruct it from a full [`crate::tree_db::StoredMerkleTree`] indexer.
     Withdraw {
         amount: u64,
+        /// Fee paid to the relayer that submits the transaction; the recipient
+        /// receives `amount - fee`. Bound in the proof so a relayer can't inflate it.
+        fee: u64,
         root: [u8; 32],
         nullifier_hash: [u8; 32],
         recipient: [u8; 32],
         proof: Vec<u8>,
     },
+
+    /// Rate-limited withdrawal. Reveals one Shamir share `(share_x, share_y)` on
+    /// the member's per-epoch line together with the RLN `nullifier`; a second
+    /// share for the same `(nullifier, epoch)` makes the secret recoverable.
+    WithdrawRLN {
+        amount: u64,
+        root: [u8; 32],
+        epoch: u64,
+        share_x: [u8; 32],
+        share_y: [u8; 32],
+        nullifier: [u8; 32],
+        recipient: [u8; 32],
+        proof: Vec<u8>,
+    },
+
+    /// Slash a member who exceeded their per-epoch quota by revealing a second
+    /// share. Reconstructs the identity secret from the stored and supplied
+    /// shares and records it.
+    Slash {
+        epoch: u64,
+        nullifier: [u8; 32],
+        share_x: [u8; 32],
+        share_y: [u8; 32],
+    },
 }
@@ -13,6 +13,10 @@ pub mod state;
 pub mod utils;
 pub mod verifier;
 pub mod poseidon;
+pub mod note;
+pub mod builder;
+pub mod rln;
+pub mod tree_db;
 
 // Entrypoint
 entrypoint!(process_instruction);
@@ -0,0 +1,83 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::rngs::OsRng;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+// Encrypted-note subsystem, following the in-band secret distribution used by
+// Zcash's Orchard (`zcash_note_encryption`): each shielded output carries a
+// ciphertext sealed against the recipient's public key plus the ephemeral
+// public key needed to re-derive the shared secret. Wallets trial-decrypt the
+// outputs to discover incoming funds without any off-chain side channel.
+
+/// Single-use nonce. The ephemeral key is fresh per note, so a fixed nonce is
+/// safe — the AEAD key never repeats.
+const NOTE_NONCE: [u8; 12] = [0u8; 12];
+
+/// The secret contents of a shielded note.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct NotePlaintext {
+    /// Value of the note.
+    pub amount: u64,
+    /// Blinding factor / randomness used in the commitment.
+    pub blinding: [u8; 32],
+    /// Optional free-form memo carried to the recipient.
+    pub memo: Vec<u8>,
+}
+
+/// Derive the AEAD cipher from a 32-byte ECDH shared secret.
+fn cipher_from_shared(shared: &[u8; 32]) -> ChaCha20Poly1305 {
+    ChaCha20Poly1305::new(Key::from_slice(shared))
+}
+
+/// Encrypt `plaintext` for `recipient_pk`, returning the ephemeral public key
+/// and the AEAD ciphertext to be recorded alongside the commitment.
+pub fn encrypt_note(recipient_pk: &[u8; 32], plaintext: &NotePlaintext) -> ([u8; 32], Vec<u8>) {
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let epk = PublicKey::from(&ephemeral_secret);
+
+    let recipient = PublicKey::from(*recipient_pk);
+    let shared = ephemeral_secret.diffie_hellman(&recipient);
+
+    let cipher = cipher_from_shared(shared.as_bytes());
+    let message = borsh::to_vec(plaintext).expect("note plaintext serializes");
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&NOTE_NONCE), message.as_ref())
+        .expect("AEAD encryption does not fail");
+
+    (*epk.as_bytes(), ciphertext)
+}
+
+/// Attempt to decrypt a note using the recipient's secret key. Returns `None`
+/// if the AEAD tag does not verify (i.e. the note is not for this holder) or
+/// the plaintext does not deserialize.
+pub fn try_decrypt_note(
+    recipient_sk: &[u8; 32],
+    epk: &[u8; 32],
+    ciphertext: &[u8],
+) -> Option<NotePlaintext> {
+    let secret = StaticSecret::from(*recipient_sk);
+    let ephemeral = PublicKey::from(*epk);
+    let shared = secret.diffie_hellman(&ephemeral);
+
+    let cipher = cipher_from_shared(shared.as_bytes());
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&NOTE_NONCE), ciphertext)
+        .ok()?;
+
+    NotePlaintext::try_from_slice(&plaintext).ok()
+}
+
+/// Trial-decrypt a batch of outputs and return the notes belonging to the
+/// holder of `recipient_sk`.
+pub fn scan_notes(
+    recipient_sk: &[u8; 32],
+    outputs: &[([u8; 32], Vec<u8>)],
+) -> Vec<NotePlaintext> {
+    outputs
+        .iter()
+        .filter_map(|(epk, ciphertext)| try_decrypt_note(recipient_sk, epk, ciphertext))
+        .collect()
+}
@@ -33,6 +33,26 @@ fn get_poseidon_width_2() -> Result<Poseidon<Fr>, ProgramError> {
         .map_err(|_| ProgramError::Custom(1))
 }
 
+// Create new Poseidon instance for width 4
+fn get_poseidon_width_4() -> Result<Poseidon<Fr>, ProgramError> {
+    // Ensure initialization is performed
+    Lazy::force(&POSEIDON_INIT);
+
+    // Create a new instance each time (they're lightweight)
+    Poseidon::<Fr>::new_circom(4)
+        .map_err(|_| ProgramError::Custom(1))
+}
+
+// Create new Poseidon instance for width 8
+fn get_poseidon_width_8() -> Result<Poseidon<Fr>, ProgramError> {
+    // Ensure initialization is performed
+    Lazy::force(&POSEIDON_INIT);
+
+    // Create a new instance each time (they're lightweight)
+    Poseidon::<Fr>::new_circom(8)
+        .map_err(|_| ProgramError::Custom(1))
+}
+
 // Simple wrapper for hashing a single input
 pub fn hash_1(input: &[u8; 32]) -> Result<[u8; 32], ProgramError> {
     let mut poseidon = get_poseidon_width_1()?;
@@ -54,6 +74,108 @@ pub fn hash_left_right(left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32], Pr
     hash_2(left, right)
 }
 
+// Implementation for hashing four inputs (arity-4 / quaternary trees)
+pub fn hash_4(inputs: &[[u8; 32]; 4]) -> Result<[u8; 32], ProgramError> {
+    let mut poseidon = get_poseidon_width_4()?;
+
+    poseidon.hash_bytes_be(&[&inputs[0], &inputs[1], &inputs[2], &inputs[3]])
+        .map_err(|_| ProgramError::Custom(1))
+}
+
+// Implementation for hashing eight inputs (arity-8 / octal trees)
+pub fn hash_8(inputs: &[[u8; 32]; 8]) -> Result<[u8; 32], ProgramError> {
+    let mut poseidon = get_poseidon_width_8()?;
+
+    poseidon
+        .hash_bytes_be(&[
+            &inputs[0], &inputs[1], &inputs[2], &inputs[3], &inputs[4], &inputs[5], &inputs[6],
+            &inputs[7],
+        ])
+        .map_err(|_| ProgramError::Custom(1))
+}
+
+/// Hash the ordered children of one arity-`n` tree node. Dispatches to the
+/// fixed-width hasher matching `inputs.len()`; only the circom widths wired up
+/// here (2, 4, 8) are supported.
+pub fn hash_arity(inputs: &[[u8; 32]]) -> Result<[u8; 32], ProgramError> {
+    match inputs.len() {
+        2 => hash_2(&inputs[0], &inputs[1]),
+        4 => hash_4(&[inputs[0], inputs[1], inputs[2], inputs[3]]),
+        8 => hash_8(&[
+            inputs[0], inputs[1], inputs[2], inputs[3], inputs[4], inputs[5], inputs[6], inputs[7],
+        ]),
+        _ => Err(PoseidonError::InvalidInputLength.into()),
+    }
+}
+
+/// Compute a Merkle root for a tree of configurable `arity` (2, 4 or 8).
+///
+/// Each level consumes `arity - 1` sibling nodes from `path` (in order) plus a
+/// single position byte from `positions` in `0..arity` that selects where the
+/// current node slots in among the `arity` ordered children before hashing with
+/// [`hash_arity`]. A depth-`d` arity-`k` tree covers `k^d` leaves with `d`
+/// hashes per proof, shortening the path versus the binary
+/// [`compute_merkle_root`].
+pub fn compute_merkle_root_arity(
+    leaf: &[u8; 32],
+    path: &[[u8; 32]],
+    positions: &[u8],
+    arity: u8,
+) -> Result<[u8; 32], ProgramError> {
+    if arity < 2 {
+        return Err(PoseidonError::InvalidInputLength.into());
+    }
+
+    // Each level supplies exactly `arity - 1` siblings.
+    let per_level = arity as usize - 1;
+    if path.is_empty() || path.len() % per_level != 0 {
+        return Err(PoseidonError::InvalidInputLength.into());
+    }
+
+    let levels = path.len() / per_level;
+    if positions.len() < levels {
+        return Err(PoseidonError::InvalidInputLength.into());
+    }
+
+    let mut current = *leaf;
+
+    for level in 0..levels {
+        let position = positions[level];
+        if position as usize >= arity as usize {
+            return Err(PoseidonError::InvalidInputLength.into());
+        }
+
+        let siblings = &path[level * per_level..level * per_level + per_level];
+
+        // Place `current` at slot `position`, filling the remaining slots with
+        // the siblings in order.
+        let mut children = vec![[0u8; 32]; arity as usize];
+        let mut sibling_iter = siblings.iter();
+        for (slot, child) in children.iter_mut().enumerate() {
+            if slot == position as usize {
+                *child = current;
+            } else {
+                *child = *sibling_iter.next().unwrap();
+            }
+        }
+
+        current = hash_arity(&children)?;
+    }
+
+    Ok(current)
+}
+
+/// Compute a Merkle root for a quaternary (arity-4) tree. Thin wrapper over
+/// [`compute_merkle_root_arity`] fixed at arity 4, kept for the quaternary
+/// proof path.
+pub fn compute_merkle_root_arity4(
+    leaf: &[u8; 32],
+    path: &[[u8; 32]],
+    indices: &[u8],
+) -> Result<[u8; 32], ProgramError> {
+    compute_merkle_root_arity(leaf, path, indices, 4)
+}
+
 // Compute Merkle root from leaf and path
 pub fn compute_merkle_root(
     leaf: &[u8; 32],
@@ -1,21 +1,44 @@
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::BorshDeserialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
+    log::sol_log_data,
     program_error::ProgramError,
     pubkey::Pubkey,
     program::{invoke, invoke_signed},
     system_instruction,
-    sysvar::{rent::Rent, Sysvar},
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
 use crate::{
     instruction::PrivacyInstruction,
-    state::{Pool, MerkleTree, Nullifier},
+    state::{BorshState, Pool, MerkleTree, Nullifier, RlnNullifier, SlashRecord},
     error::PrivacyError,
-    utils::{find_pool_pda, find_merkle_tree_pda, find_nullifier_pda, Utils},
+    rln::{self, ALLOWED_EPOCH_WINDOW},
+    utils::{find_pool_pda, find_merkle_tree_pda, find_nullifier_pda, find_rln_nullifier_pda, find_slash_pda, find_withdraw_authority_pda, Utils},
     verifier::Verifier,
 };
 
+/// Maximum length of the opaque encrypted-note ciphertext accepted by
+/// [`Processor::process_shield`]. Large enough for the note plaintext plus the
+/// AEAD tag and a modest memo; oversized ciphertexts are rejected.
+pub const MAX_ENCRYPTED_NOTE_LEN: usize = 512;
+
+/// Route a runtime tree height to the matching `MerkleTree<DEPTH>`
+/// monomorphization. `$call` is a const-generic function invoked as
+/// `$call::<DEPTH>(args...)`; unsupported heights are rejected with
+/// `InvalidArgument`.
+macro_rules! dispatch_by_depth {
+    ($height:expr, $call:path $(, $arg:expr )* $(,)?) => {
+        match $height {
+            16 => $call::<16>($($arg),*),
+            20 => $call::<20>($($arg),*),
+            24 => $call::<24>($($arg),*),
+            32 => $call::<32>($($arg),*),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    };
+}
+
 pub struct Processor;
 
 impl Processor {
@@ -23,45 +46,122 @@ impl Processor {
         let instruction = PrivacyInstruction::try_from_slice(instruction_data)?;
         
         match instruction {
-            PrivacyInstruction::Initialize { merkle_tree_height } => {
-                Self::process_initialize(program_id, accounts, merkle_tree_height)
+            PrivacyInstruction::Initialize { merkle_tree_height, mint } => {
+                Self::process_initialize(program_id, accounts, merkle_tree_height, mint)
+            },
+            PrivacyInstruction::Shield { amount, commitment, encrypted_note } => {
+                Self::process_shield(program_id, accounts, amount, commitment, encrypted_note)
             },
-            PrivacyInstruction::Shield { amount, commitment } => {
-                Self::process_shield(program_id, accounts, amount, commitment)
+            PrivacyInstruction::ShieldBatch { amounts, commitments } => {
+                Self::process_shield_batch(program_id, accounts, amounts, commitments)
             },
-            PrivacyInstruction::Withdraw { amount, root, nullifier_hash, recipient, proof } => {
-                Self::process_withdraw(program_id, accounts, amount, root, nullifier_hash, recipient, proof)
+            PrivacyInstruction::Withdraw { amount, fee, root, nullifier_hash, recipient, proof } => {
+                Self::process_withdraw(program_id, accounts, amount, fee, root, nullifier_hash, recipient, proof)
+            },
+            PrivacyInstruction::WithdrawRLN { amount, root, epoch, share_x, share_y, nullifier, recipient, proof } => {
+                Self::process_withdraw_rln(program_id, accounts, amount, root, epoch, share_x, share_y, nullifier, recipient, proof)
+            },
+            PrivacyInstruction::Slash { epoch, nullifier, share_x, share_y } => {
+                Self::process_slash(program_id, accounts, epoch, nullifier, share_x, share_y)
             },
         }
     }
     
+    /// Serialize a fresh `MerkleTree<DEPTH>`, used to size and seed its account.
+    fn new_tree_bytes<const DEPTH: usize>() -> Result<Vec<u8>, ProgramError> {
+        borsh::to_vec(&MerkleTree::<DEPTH>::new()).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Load the depth-`DEPTH` tree, append `commitment`, persist it, and return
+    /// the leaf's index.
+    fn insert_commitment<const DEPTH: usize>(
+        merkle_tree_info: &AccountInfo,
+        commitment: &[u8; 32],
+    ) -> Result<u32, ProgramError> {
+        let mut merkle_tree = MerkleTree::<DEPTH>::load(merkle_tree_info)?;
+        let leaf_index = merkle_tree.current_index;
+        merkle_tree.insert(commitment)?;
+        merkle_tree.save(merkle_tree_info)?;
+        Ok(leaf_index)
+    }
+
+    /// Load the depth-`DEPTH` tree, append a block of `commitments` in one pass,
+    /// persist it, and return the index of the first appended leaf.
+    fn insert_commitments<const DEPTH: usize>(
+        merkle_tree_info: &AccountInfo,
+        commitments: &[[u8; 32]],
+    ) -> Result<u32, ProgramError> {
+        let mut merkle_tree = MerkleTree::<DEPTH>::load(merkle_tree_info)?;
+        let first_index = merkle_tree.current_index;
+        merkle_tree.insert_batch(commitments)?;
+        merkle_tree.save(merkle_tree_info)?;
+        Ok(first_index)
+    }
+
+    /// Load the depth-`DEPTH` tree and report whether `root` is in its recent
+    /// history window.
+    fn tree_knows_root<const DEPTH: usize>(
+        merkle_tree_info: &AccountInfo,
+        root: &[u8; 32],
+    ) -> Result<bool, ProgramError> {
+        Ok(MerkleTree::<DEPTH>::load(merkle_tree_info)?.is_known_root(root))
+    }
+
+    /// Verify that an SPL token account is for `mint` by parsing the mint
+    /// `Pubkey` from the first 32 bytes of its data.
+    fn check_token_mint(token_account: &AccountInfo, mint: &[u8; 32]) -> ProgramResult {
+        let data = token_account.data.borrow();
+        if data.len() < 32 {
+            return Err(PrivacyError::InvalidMint.into());
+        }
+        let account_mint = Pubkey::try_from(&data[..32]).map_err(|_| PrivacyError::InvalidMint)?;
+        if account_mint != Pubkey::new_from_array(*mint) {
+            return Err(PrivacyError::InvalidMint.into());
+        }
+        Ok(())
+    }
+
     fn process_initialize(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         merkle_tree_height: u8,
+        mint: Option<[u8; 32]>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         
         let payer_info = next_account_info(account_info_iter)?;
         let pool_info = next_account_info(account_info_iter)?;
         let merkle_tree_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
         let rent_info = next_account_info(account_info_iter)?;
-        
+
         let (pool_pda, pool_bump) = find_pool_pda(program_id);
         if pool_pda != *pool_info.key {
             return Err(PrivacyError::InvalidPool.into());
         }
-        
+
         let (merkle_tree_pda, merkle_tree_bump) = find_merkle_tree_pda(program_id);
         if merkle_tree_pda != *merkle_tree_info.key {
             return Err(ProgramError::InvalidAccountData);
         }
-        
+
+        let (vault_pda, vault_bump) = find_withdraw_authority_pda(program_id);
+        if vault_pda != *vault_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
         let rent = Rent::from_account_info(rent_info)?;
-        let pool_size = std::mem::size_of::<Pool>();
+
+        let pool = Pool {
+            is_initialized: true,
+            merkle_tree_height,
+            total_amount: 0,
+            mint,
+        };
+        let pool_size = borsh::to_vec(&pool)?.len();
         let pool_lamports = rent.minimum_balance(pool_size);
-        
+
         invoke_signed(
             &system_instruction::create_account(
                 payer_info.key,
@@ -73,18 +173,15 @@ impl Processor {
             &[payer_info.clone(), pool_info.clone(), system_program_info.clone()],
             &[&[b"privacy_pool", &[pool_bump]]],
         )?;
-        
-        let pool = Pool {
-            is_initialized: true,
-            merkle_tree_height,
-            total_amount: 0,
-        };
-        
-        pool.serialize(&mut *pool_info.data.borrow_mut())?;
-        
-        let merkle_tree_size = std::mem::size_of::<MerkleTree>();
+
+        pool.save_exempt(pool_info, &rent)?;
+
+        // Route the runtime height to the matching const-generic tree so the
+        // account is sized for that depth exactly.
+        let merkle_tree_bytes = dispatch_by_depth!(merkle_tree_height, Self::new_tree_bytes)?;
+        let merkle_tree_size = merkle_tree_bytes.len();
         let merkle_tree_lamports = rent.minimum_balance(merkle_tree_size);
-        
+
         invoke_signed(
             &system_instruction::create_account(
                 payer_info.key,
@@ -96,10 +193,25 @@ impl Processor {
             &[payer_info.clone(), merkle_tree_info.clone(), system_program_info.clone()],
             &[&[b"merkle_tree", &[merkle_tree_bump]]],
         )?;
-        
-        let merkle_tree = MerkleTree::new(merkle_tree_height);
-        merkle_tree.serialize(&mut *merkle_tree_info.data.borrow_mut())?;
-        
+
+        merkle_tree_info.data.borrow_mut().copy_from_slice(&merkle_tree_bytes);
+
+        // Fund the pool vault under the withdraw-authority PDA. It stays
+        // system-owned (no data) so the program can later debit it with a
+        // system transfer signed by the authority seeds.
+        let vault_lamports = rent.minimum_balance(0);
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                &vault_pda,
+                vault_lamports,
+                0,
+                &solana_program::system_program::id(),
+            ),
+            &[payer_info.clone(), vault_info.clone(), system_program_info.clone()],
+            &[&[b"withdraw_authority", &[vault_bump]]],
+        )?;
+
         Ok(())
     }
     
@@ -108,63 +220,214 @@ impl Processor {
         accounts: &[AccountInfo],
         amount: u64,
         commitment: [u8; 32],
+        encrypted_note: Vec<u8>,
     ) -> ProgramResult {
+        // Bound the opaque ciphertext so a single shield can't bloat the log.
+        if encrypted_note.len() > MAX_ENCRYPTED_NOTE_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
         let account_info_iter = &mut accounts.iter();
         
         let payer_info = next_account_info(account_info_iter)?;
         let pool_info = next_account_info(account_info_iter)?;
         let merkle_tree_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
-        
+
         let (pool_pda, _) = find_pool_pda(program_id);
         if pool_pda != *pool_info.key {
             return Err(PrivacyError::InvalidPool.into());
         }
-        
+
         let (merkle_tree_pda, _) = find_merkle_tree_pda(program_id);
         if merkle_tree_pda != *merkle_tree_info.key {
             return Err(ProgramError::InvalidAccountData);
         }
-        
-        invoke(
-            &system_instruction::transfer(payer_info.key, &pool_pda, amount),
-            &[payer_info.clone(), pool_info.clone(), system_program_info.clone()],
-        )?;
-        
-        let mut pool = Pool::try_from_slice(&pool_info.data.borrow())?;
+
+        let (vault_pda, _) = find_withdraw_authority_pda(program_id);
+        if vault_pda != *vault_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut pool = Pool::load(pool_info)?;
+
+        match pool.mint {
+            // SPL-token pool: move tokens from the depositor's token account
+            // into the pool's token account owned by the withdraw authority.
+            Some(mint) => {
+                let depositor_token_info = next_account_info(account_info_iter)?;
+                let pool_token_info = next_account_info(account_info_iter)?;
+                let token_program_info = next_account_info(account_info_iter)?;
+
+                Self::check_token_mint(depositor_token_info, &mint)?;
+                Self::check_token_mint(pool_token_info, &mint)?;
+
+                invoke(
+                    &spl_token::instruction::transfer(
+                        token_program_info.key,
+                        depositor_token_info.key,
+                        pool_token_info.key,
+                        payer_info.key,
+                        &[],
+                        amount,
+                    )?,
+                    &[
+                        depositor_token_info.clone(),
+                        pool_token_info.clone(),
+                        payer_info.clone(),
+                        token_program_info.clone(),
+                    ],
+                )?;
+            }
+            // Native-SOL pool: deposit lamports into the vault.
+            None => {
+                invoke(
+                    &system_instruction::transfer(payer_info.key, &vault_pda, amount),
+                    &[payer_info.clone(), vault_info.clone(), system_program_info.clone()],
+                )?;
+            }
+        }
+
         pool.total_amount += amount;
-        pool.serialize(&mut *pool_info.data.borrow_mut())?;
-        
-        let mut merkle_tree = MerkleTree::try_from_slice(&merkle_tree_info.data.borrow())?;
-        merkle_tree.insert(&commitment)?;
-        merkle_tree.serialize(&mut *merkle_tree_info.data.borrow_mut())?;
-        
+        pool.save(pool_info)?;
+
+        // The commitment lands at the tree's next free slot; bind the ciphertext
+        // to that leaf index so wallets can rebuild their witness on-chain.
+        let leaf_index =
+            dispatch_by_depth!(pool.merkle_tree_height, Self::insert_commitment, merkle_tree_info, &commitment)?;
+
+        if !encrypted_note.is_empty() {
+            sol_log_data(&[&leaf_index.to_le_bytes(), &encrypted_note]);
+        }
+
         Ok(())
     }
-    
+
+    fn process_shield_batch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amounts: Vec<u64>,
+        commitments: Vec<[u8; 32]>,
+    ) -> ProgramResult {
+        // The two vectors are positional pairs and must line up.
+        if amounts.len() != commitments.len() || commitments.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let total_amount = amounts
+            .iter()
+            .try_fold(0u64, |acc, a| acc.checked_add(*a))
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        let account_info_iter = &mut accounts.iter();
+
+        let payer_info = next_account_info(account_info_iter)?;
+        let pool_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        let (pool_pda, _) = find_pool_pda(program_id);
+        if pool_pda != *pool_info.key {
+            return Err(PrivacyError::InvalidPool.into());
+        }
+
+        let (merkle_tree_pda, _) = find_merkle_tree_pda(program_id);
+        if merkle_tree_pda != *merkle_tree_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (vault_pda, _) = find_withdraw_authority_pda(program_id);
+        if vault_pda != *vault_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut pool = Pool::load(pool_info)?;
+
+        match pool.mint {
+            // SPL-token pool: move the aggregated deposit from the depositor's
+            // token account into the pool's token account.
+            Some(mint) => {
+                let depositor_token_info = next_account_info(account_info_iter)?;
+                let pool_token_info = next_account_info(account_info_iter)?;
+                let token_program_info = next_account_info(account_info_iter)?;
+
+                Self::check_token_mint(depositor_token_info, &mint)?;
+                Self::check_token_mint(pool_token_info, &mint)?;
+
+                invoke(
+                    &spl_token::instruction::transfer(
+                        token_program_info.key,
+                        depositor_token_info.key,
+                        pool_token_info.key,
+                        payer_info.key,
+                        &[],
+                        total_amount,
+                    )?,
+                    &[
+                        depositor_token_info.clone(),
+                        pool_token_info.clone(),
+                        payer_info.clone(),
+                        token_program_info.clone(),
+                    ],
+                )?;
+            }
+            // Native-SOL pool: deposit the aggregated lamports into the vault.
+            None => {
+                invoke(
+                    &system_instruction::transfer(payer_info.key, &vault_pda, total_amount),
+                    &[payer_info.clone(), vault_info.clone(), system_program_info.clone()],
+                )?;
+            }
+        }
+
+        pool.total_amount += total_amount;
+        pool.save(pool_info)?;
+
+        // Append the whole block with a single batched tree update.
+        dispatch_by_depth!(pool.merkle_tree_height, Self::insert_commitments, merkle_tree_info, &commitments)?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn process_withdraw(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         amount: u64,
+        fee: u64,
         root: [u8; 32],
         nullifier_hash: [u8; 32],
         recipient: [u8; 32],
         proof: Vec<u8>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        
+
         let payer_info = next_account_info(account_info_iter)?;
         let pool_info = next_account_info(account_info_iter)?;
         let merkle_tree_info = next_account_info(account_info_iter)?;
         let nullifier_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
         let recipient_info = next_account_info(account_info_iter)?;
+        let relayer_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
         let rent_info = next_account_info(account_info_iter)?;
-        
-        let (pool_pda, pool_bump) = find_pool_pda(program_id);
+
+        // The relayer keeps `fee`; the recipient must net a positive amount.
+        if fee >= amount {
+            return Err(PrivacyError::InsufficientFunds.into());
+        }
+
+        let (pool_pda, _pool_bump) = find_pool_pda(program_id);
         if pool_pda != *pool_info.key {
             return Err(PrivacyError::InvalidPool.into());
         }
+
+        let (vault_pda, vault_bump) = find_withdraw_authority_pda(program_id);
+        if vault_pda != *vault_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
         
         let (merkle_tree_pda, _) = find_merkle_tree_pda(program_id);
         if merkle_tree_pda != *merkle_tree_info.key {
@@ -181,33 +444,43 @@ impl Processor {
             return Err(PrivacyError::InvalidRecipient.into());
         }
         
-        let merkle_tree = MerkleTree::try_from_slice(&merkle_tree_info.data.borrow())?;
-        if merkle_tree.root != root {
+        let tree_height = Pool::load(pool_info)?.merkle_tree_height;
+        let known_root = dispatch_by_depth!(tree_height, Self::tree_knows_root, merkle_tree_info, &root)?;
+        if !known_root {
             return Err(PrivacyError::InvalidRoot.into());
         }
-        
+
         if !nullifier_info.data_is_empty() {
             return Err(PrivacyError::NullifierAlreadyUsed.into());
         }
         
-        // In production, this would use the actual verification key and public inputs
-        let verification_key = &[0u8; 32]; // Placeholder
-        let public_inputs = &[0u8; 32]; // Placeholder
-        
-        let is_valid = Verifier::verify_withdrawal_proof(
-            &proof,
-            public_inputs,
-            verification_key,
-        )?;
-        
+        // Bind the public inputs the circuit proves over: root, nullifier hash,
+        // recipient, amount, and fee (each a 32-byte big-endian field element).
+        let mut public_inputs = Vec::with_capacity(5 * 32);
+        public_inputs.extend_from_slice(&root);
+        public_inputs.extend_from_slice(&nullifier_hash);
+        public_inputs.extend_from_slice(&recipient);
+        let mut amount_fe = [0u8; 32];
+        amount_fe[24..].copy_from_slice(&amount.to_be_bytes());
+        public_inputs.extend_from_slice(&amount_fe);
+        let mut fee_fe = [0u8; 32];
+        fee_fe[24..].copy_from_slice(&fee.to_be_bytes());
+        public_inputs.extend_from_slice(&fee_fe);
+
+        let is_valid = Verifier::verify_withdrawal_proof(&proof, &public_inputs, &[])?;
+
         if !is_valid {
             return Err(PrivacyError::InvalidProof.into());
         }
         
         let rent = Rent::from_account_info(rent_info)?;
-        let nullifier_size = std::mem::size_of::<Nullifier>();
+        let nullifier = Nullifier {
+            is_initialized: true,
+            nullifier_hash,
+        };
+        let nullifier_size = borsh::to_vec(&nullifier)?.len();
         let nullifier_lamports = rent.minimum_balance(nullifier_size);
-        
+
         invoke_signed(
             &system_instruction::create_account(
                 payer_info.key,
@@ -219,29 +492,313 @@ impl Processor {
             &[payer_info.clone(), nullifier_info.clone(), system_program_info.clone()],
             &[&[b"nullifier", &nullifier_hash, &[nullifier_bump]]],
         )?;
-        
-        let nullifier = Nullifier {
+
+        nullifier.save_exempt(nullifier_info, &rent)?;
+
+        let mut pool = Pool::load(pool_info)?;
+
+        if pool.total_amount < amount {
+            return Err(PrivacyError::InsufficientFunds.into());
+        }
+
+        pool.total_amount -= amount;
+        pool.save(pool_info)?;
+
+        // The recipient nets `amount - fee`; the relayer keeps `fee`.
+        let recipient_amount = amount - fee;
+
+        match pool.mint {
+            // SPL-token pool: move tokens out of the pool token account, signed
+            // by the withdraw authority, paying recipient and relayer accounts.
+            Some(mint) => {
+                let pool_token_info = next_account_info(account_info_iter)?;
+                let recipient_token_info = next_account_info(account_info_iter)?;
+                let relayer_token_info = next_account_info(account_info_iter)?;
+                let token_program_info = next_account_info(account_info_iter)?;
+
+                Self::check_token_mint(pool_token_info, &mint)?;
+                Self::check_token_mint(recipient_token_info, &mint)?;
+                Self::check_token_mint(relayer_token_info, &mint)?;
+
+                invoke_signed(
+                    &spl_token::instruction::transfer(
+                        token_program_info.key,
+                        pool_token_info.key,
+                        recipient_token_info.key,
+                        &vault_pda,
+                        &[],
+                        recipient_amount,
+                    )?,
+                    &[
+                        pool_token_info.clone(),
+                        recipient_token_info.clone(),
+                        vault_info.clone(),
+                        token_program_info.clone(),
+                    ],
+                    &[&[b"withdraw_authority", &[vault_bump]]],
+                )?;
+
+                if fee > 0 {
+                    invoke_signed(
+                        &spl_token::instruction::transfer(
+                            token_program_info.key,
+                            pool_token_info.key,
+                            relayer_token_info.key,
+                            &vault_pda,
+                            &[],
+                            fee,
+                        )?,
+                        &[
+                            pool_token_info.clone(),
+                            relayer_token_info.clone(),
+                            vault_info.clone(),
+                            token_program_info.clone(),
+                        ],
+                        &[&[b"withdraw_authority", &[vault_bump]]],
+                    )?;
+                }
+            }
+            // Native-SOL pool: debit the vault directly for each leg.
+            None => {
+                invoke_signed(
+                    &system_instruction::transfer(&vault_pda, &recipient_pubkey, recipient_amount),
+                    &[vault_info.clone(), recipient_info.clone(), system_program_info.clone()],
+                    &[&[b"withdraw_authority", &[vault_bump]]],
+                )?;
+
+                if fee > 0 {
+                    invoke_signed(
+                        &system_instruction::transfer(&vault_pda, relayer_info.key, fee),
+                        &[vault_info.clone(), relayer_info.clone(), system_program_info.clone()],
+                        &[&[b"withdraw_authority", &[vault_bump]]],
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_withdraw_rln(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        root: [u8; 32],
+        epoch: u64,
+        share_x: [u8; 32],
+        share_y: [u8; 32],
+        nullifier: [u8; 32],
+        recipient: [u8; 32],
+        proof: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let payer_info = next_account_info(account_info_iter)?;
+        let pool_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let nullifier_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let recipient_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+
+        let (pool_pda, _pool_bump) = find_pool_pda(program_id);
+        if pool_pda != *pool_info.key {
+            return Err(PrivacyError::InvalidPool.into());
+        }
+
+        let (vault_pda, vault_bump) = find_withdraw_authority_pda(program_id);
+        if vault_pda != *vault_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (merkle_tree_pda, _) = find_merkle_tree_pda(program_id);
+        if merkle_tree_pda != *merkle_tree_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // The RLN nullifier PDA is keyed by `(nullifier, epoch)`, so a member
+        // gets one slot per epoch rather than one slot for all time.
+        let (rln_pda, rln_bump) = find_rln_nullifier_pda(program_id, &nullifier, epoch);
+        if rln_pda != *nullifier_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let recipient_pubkey = Utils::bytes_to_pubkey(&recipient);
+        if recipient_pubkey != *recipient_info.key {
+            return Err(PrivacyError::InvalidRecipient.into());
+        }
+
+        // Reject signals for epochs outside the currently accepted window.
+        let clock = Clock::from_account_info(clock_info)?;
+        let current_epoch = clock.epoch;
+        if epoch.abs_diff(current_epoch) > ALLOWED_EPOCH_WINDOW {
+            return Err(PrivacyError::EpochOutOfRange.into());
+        }
+
+        let tree_height = Pool::load(pool_info)?.merkle_tree_height;
+        let known_root = dispatch_by_depth!(tree_height, Self::tree_knows_root, merkle_tree_info, &root)?;
+        if !known_root {
+            return Err(PrivacyError::InvalidRoot.into());
+        }
+
+        // If this `(nullifier, epoch)` has already signalled, the member has
+        // exhausted their quota; a distinct share is the slashable event.
+        if !nullifier_info.data_is_empty() {
+            return Err(PrivacyError::RateLimitExceeded.into());
+        }
+
+        // Bind the RLN public inputs: root, epoch, share point, and nullifier.
+        let mut public_inputs = Vec::with_capacity(5 * 32);
+        public_inputs.extend_from_slice(&root);
+        public_inputs.extend_from_slice(&rln::epoch_to_field(epoch));
+        public_inputs.extend_from_slice(&share_x);
+        public_inputs.extend_from_slice(&share_y);
+        public_inputs.extend_from_slice(&nullifier);
+
+        let is_valid = Verifier::verify_withdrawal_proof(&proof, &public_inputs, &[])?;
+        if !is_valid {
+            return Err(PrivacyError::InvalidProof.into());
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+        let record = RlnNullifier {
             is_initialized: true,
-            nullifier_hash,
+            epoch,
+            nullifier,
+            share_x,
+            share_y,
         };
-        
-        nullifier.serialize(&mut *nullifier_info.data.borrow_mut())?;
-        
-        let mut pool = Pool::try_from_slice(&pool_info.data.borrow())?;
-        
+        let record_size = borsh::to_vec(&record)?.len();
+        let record_lamports = rent.minimum_balance(record_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                &rln_pda,
+                record_lamports,
+                record_size as u64,
+                program_id,
+            ),
+            &[payer_info.clone(), nullifier_info.clone(), system_program_info.clone()],
+            &[&[b"rln_nullifier", &nullifier, &epoch.to_le_bytes(), &[rln_bump]]],
+        )?;
+
+        record.save_exempt(nullifier_info, &rent)?;
+
+        let mut pool = Pool::load(pool_info)?;
         if pool.total_amount < amount {
             return Err(PrivacyError::InsufficientFunds.into());
         }
-        
         pool.total_amount -= amount;
-        pool.serialize(&mut *pool_info.data.borrow_mut())?;
-        
+        pool.save(pool_info)?;
+
+        match pool.mint {
+            Some(mint) => {
+                let pool_token_info = next_account_info(account_info_iter)?;
+                let recipient_token_info = next_account_info(account_info_iter)?;
+                let token_program_info = next_account_info(account_info_iter)?;
+
+                Self::check_token_mint(pool_token_info, &mint)?;
+                Self::check_token_mint(recipient_token_info, &mint)?;
+
+                invoke_signed(
+                    &spl_token::instruction::transfer(
+                        token_program_info.key,
+                        pool_token_info.key,
+                        recipient_token_info.key,
+                        &vault_pda,
+                        &[],
+                        amount,
+                    )?,
+                    &[
+                        pool_token_info.clone(),
+                        recipient_token_info.clone(),
+                        vault_info.clone(),
+                        token_program_info.clone(),
+                    ],
+                    &[&[b"withdraw_authority", &[vault_bump]]],
+                )?;
+            }
+            None => {
+                invoke_signed(
+                    &system_instruction::transfer(&vault_pda, &recipient_pubkey, amount),
+                    &[vault_info.clone(), recipient_info.clone(), system_program_info.clone()],
+                    &[&[b"withdraw_authority", &[vault_bump]]],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reconstruct a cheater's identity secret from the share already recorded
+    /// for `(nullifier, epoch)` and a second, distinct share, then persist it.
+    fn process_slash(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        epoch: u64,
+        nullifier: [u8; 32],
+        share_x: [u8; 32],
+        share_y: [u8; 32],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let payer_info = next_account_info(account_info_iter)?;
+        let rln_info = next_account_info(account_info_iter)?;
+        let slash_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        let (rln_pda, _) = find_rln_nullifier_pda(program_id, &nullifier, epoch);
+        if rln_pda != *rln_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (slash_pda, slash_bump) = find_slash_pda(program_id, &nullifier);
+        if slash_pda != *slash_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let recorded = RlnNullifier::load(rln_info)?;
+
+        // Two shares sharing an abscissa are the same signal, not a violation.
+        if recorded.share_x == share_x {
+            return Err(PrivacyError::RateLimitExceeded.into());
+        }
+
+        let recovered_secret = rln::recover_secret(
+            (&recorded.share_x, &recorded.share_y),
+            (&share_x, &share_y),
+        )?;
+        let identity_commitment = rln::identity_commitment(&recovered_secret)?;
+
+        let rent = Rent::from_account_info(rent_info)?;
+        let record = SlashRecord {
+            is_initialized: true,
+            nullifier,
+            recovered_secret,
+            identity_commitment,
+        };
+        let record_size = borsh::to_vec(&record)?.len();
+        let record_lamports = rent.minimum_balance(record_size);
+
         invoke_signed(
-            &system_instruction::transfer(&pool_pda, &recipient_pubkey, amount),
-            &[pool_info.clone(), recipient_info.clone(), system_program_info.clone()],
-            &[&[b"privacy_pool", &[pool_bump]]],
+            &system_instruction::create_account(
+                payer_info.key,
+                &slash_pda,
+                record_lamports,
+                record_size as u64,
+                program_id,
+            ),
+            &[payer_info.clone(), slash_info.clone(), system_program_info.clone()],
+            &[&[b"slash", &nullifier, &[slash_bump]]],
         )?;
-        
+
+        record.save_exempt(slash_info, &rent)?;
+
         Ok(())
     }
 }
@@ -0,0 +1,88 @@
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use solana_program::program_error::ProgramError;
+
+use crate::poseidon;
+
+// Rate-Limiting Nullifiers (RLN). Each member's leaf is `hash_1(identity_secret)`.
+// Per epoch a degree-1 Shamir polynomial `f(x) = a0 + a1*x` is derived with
+// `a0 = identity_secret` and `a1 = hash_2(identity_secret, epoch)`. A withdrawal
+// signal reveals a single share `(share_x, share_y)` on that line together with
+// `nullifier = hash_1(a1)`. One share per epoch leaks nothing, but a second
+// share for the same epoch gives two points on the line and anyone can recover
+// `a0` — the member's secret — by interpolation. See Rate-Limiting Nullifiers
+// (Rasmussen et al.) and the `rln` circuits.
+
+/// Number of epochs on either side of the reference epoch that are accepted.
+pub const ALLOWED_EPOCH_WINDOW: u64 = 1;
+
+/// Encode a `u64` epoch as a 32-byte big-endian field element.
+pub fn epoch_to_field(epoch: u64) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[24..].copy_from_slice(&epoch.to_be_bytes());
+    out
+}
+
+/// The membership leaf committed to the tree for a member: `Poseidon(a0)`.
+/// After [`recover_secret`] exposes `a0`, recomputing this lets the pool match
+/// and blacklist the offending leaf.
+pub fn identity_commitment(identity_secret: &[u8; 32]) -> Result<[u8; 32], ProgramError> {
+    poseidon::hash_1(identity_secret)
+}
+
+fn to_fr(bytes: &[u8; 32]) -> Fr {
+    Fr::from_be_bytes_mod_order(bytes)
+}
+
+fn from_fr(value: Fr) -> [u8; 32] {
+    let be = value.into_bigint().to_bytes_be();
+    let mut out = [0u8; 32];
+    out[32 - be.len()..].copy_from_slice(&be);
+    out
+}
+
+/// The per-epoch polynomial coefficients for a member, as field elements.
+pub fn epoch_polynomial(identity_secret: &[u8; 32], epoch: u64) -> Result<(Fr, Fr), ProgramError> {
+    let a0 = to_fr(identity_secret);
+    let a1 = to_fr(&poseidon::hash_2(identity_secret, &epoch_to_field(epoch))?);
+    Ok((a0, a1))
+}
+
+/// Derive the full RLN signal for a withdrawal: the share abscissa, its
+/// ordinate on the epoch line, and the internal nullifier.
+pub fn signal(
+    identity_secret: &[u8; 32],
+    epoch: u64,
+    signal_hash: &[u8; 32],
+) -> Result<([u8; 32], [u8; 32], [u8; 32]), ProgramError> {
+    let (a0, a1) = epoch_polynomial(identity_secret, epoch)?;
+    let share_x = poseidon::hash_1(signal_hash)?;
+    let share_y = a0 + a1 * to_fr(&share_x);
+    let nullifier = poseidon::hash_1(&from_fr(a1))?;
+    Ok((share_x, from_fr(share_y), nullifier))
+}
+
+/// Recover the secret `a0 = f(0)` from two distinct shares on the same epoch
+/// line via Lagrange interpolation over the BN254 scalar field.
+///
+/// Returns [`ProgramError::InvalidArgument`] if the two shares share the same
+/// abscissa (the line is then under-determined).
+pub fn recover_secret(
+    share0: (&[u8; 32], &[u8; 32]),
+    share1: (&[u8; 32], &[u8; 32]),
+) -> Result<[u8; 32], ProgramError> {
+    let x0 = to_fr(share0.0);
+    let y0 = to_fr(share0.1);
+    let x1 = to_fr(share1.0);
+    let y1 = to_fr(share1.1);
+
+    let denom = x1 - x0;
+    if denom.is_zero() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Line through (x0, y0), (x1, y1) evaluated at x = 0.
+    let slope = (y1 - y0) * denom.inverse().expect("nonzero denom is invertible");
+    let a0 = y0 - slope * x0;
+    Ok(from_fr(a0))
+}
@@ -1,14 +1,62 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
+    account_info::AccountInfo,
     program_pack::{IsInitialized},
     program_error::ProgramError,
+    sysvar::rent::Rent,
 };
 
+/// Shared (de)serialization for account-backed state.
+///
+/// Implemented blanketly for every Borsh type so the on-chain `processor`
+/// and the off-chain test bank encode state exactly the same way. Hand-rolled
+/// `extend_from_slice`/`push` blocks used to live in both places and drifted;
+/// routing everything through this trait makes that impossible.
+pub trait BorshState: BorshSerialize + BorshDeserialize + Sized {
+    /// Borrow `account.data` and decode it, mapping any failure to
+    /// `InvalidAccountData`.
+    fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        Self::try_from_slice(&account.data.borrow())
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Serialize `self` into `account.data`, requiring the destination to be
+    /// exactly the serialized length.
+    fn save(&self, account: &AccountInfo) -> Result<(), ProgramError> {
+        let data = borsh::to_vec(self).map_err(|_| ProgramError::InvalidAccountData)?;
+        let mut dst = account.data.borrow_mut();
+        if dst.len() != data.len() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        dst.copy_from_slice(&data);
+        Ok(())
+    }
+
+    /// Like [`BorshState::save`], but additionally assert the account's
+    /// lamports cover rent for the serialized size.
+    fn save_exempt(&self, account: &AccountInfo, rent: &Rent) -> Result<(), ProgramError> {
+        let data = borsh::to_vec(self).map_err(|_| ProgramError::InvalidAccountData)?;
+        if account.lamports() < rent.minimum_balance(data.len()) {
+            return Err(ProgramError::AccountNotRentExempt);
+        }
+        let mut dst = account.data.borrow_mut();
+        if dst.len() != data.len() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        dst.copy_from_slice(&data);
+        Ok(())
+    }
+}
+
+impl<T: BorshSerialize + BorshDeserialize> BorshState for T {}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Pool {
     pub is_initialized: bool,
     pub merkle_tree_height: u8,
     pub total_amount: u64,
+    /// SPL mint backing this pool, or `None` for a native-SOL pool.
+    pub mint: Option<[u8; 32]>,
 }
 
 impl IsInitialized for Pool {
@@ -17,66 +65,454 @@ impl IsInitialized for Pool {
     }
 }
 
+/// Number of recent roots kept so in-flight withdrawals survive new shields.
+pub const ROOT_HISTORY_SIZE: usize = 30;
+
+/// Precomputed zero-subtree hash ladder for a binary tree: `zeros[0]` is the
+/// empty-leaf value and `zeros[i] = hash_left_right(zeros[i-1], zeros[i-1])`
+/// is the root of an empty subtree of level `i`. Length is `height + 1`.
+pub fn zero_hashes(height: u8) -> Vec<[u8; 32]> {
+    zero_hashes_arity(height, 2)
+}
+
+/// Generalization of [`zero_hashes`] to arity `k`: each level's zero hash folds
+/// `k` copies of the level below through [`crate::poseidon::hash_arity`].
+pub fn zero_hashes_arity(height: u8, arity: u8) -> Vec<[u8; 32]> {
+    let mut zeros = Vec::with_capacity(height as usize + 1);
+    zeros.push([0u8; 32]);
+    for i in 1..=height as usize {
+        let children = vec![zeros[i - 1]; arity as usize];
+        zeros.push(crate::poseidon::hash_arity(&children).expect("zero-subtree hash ladder"));
+    }
+    zeros
+}
+
+/// Fold `leaf` with the recorded `path` for `index` and report whether the
+/// result matches `root`. The per-level left/right choice is taken from the
+/// bits of `index`, matching the layout [`MerkleTree::insert`] hashes with.
+pub fn verify_path(
+    leaf: &[u8; 32],
+    index: u64,
+    path: &[[u8; 32]],
+    root: &[u8; 32],
+) -> Result<bool, ProgramError> {
+    let mut current = *leaf;
+    for (level, sibling) in path.iter().enumerate() {
+        current = if (index >> level) & 1 == 0 {
+            crate::poseidon::hash_left_right(&current, sibling)?
+        } else {
+            crate::poseidon::hash_left_right(sibling, &current)?
+        };
+    }
+    Ok(current == *root)
+}
+
+/// A fixed-depth, append-only binary Merkle tree.
+///
+/// The depth is a compile-time parameter, so `filled_subtrees`/`witness_cache`
+/// are inline arrays rather than heap vectors and the serialized length is
+/// fixed for each `DEPTH`. That length is not `std::mem::size_of`, which counts
+/// alignment padding; the account-creation path in the `processor` sizes the
+/// account from a serialized fresh instance. The capacity check in
+/// [`MerkleTree::insert`] (`current_index < 1 << DEPTH`) is likewise derived
+/// from `DEPTH`. The `Initialize` instruction still takes a runtime height and
+/// routes it to the matching monomorphization in the `processor`.
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct MerkleTree {
+pub struct MerkleTree<const DEPTH: usize> {
     pub is_initialized: bool,
-    pub height: u8,
     pub current_index: u32,
     pub root: [u8; 32],
-    pub filled_subtrees: Vec<[u8; 32]>,
+    /// Left-sibling subtree recorded at each level.
+    pub filled_subtrees: [[u8; 32]; DEPTH],
+    /// Ring buffer of the last [`ROOT_HISTORY_SIZE`] roots.
+    pub roots: [[u8; 32]; ROOT_HISTORY_SIZE],
+    /// Index into `roots` of the most recently written root.
+    pub current_root_index: u64,
+    /// Authentication-path siblings recorded for the most recently inserted
+    /// leaf, one per level. Updated on every [`MerkleTree::insert`] so a prover
+    /// can read the membership path of the frontier leaf without the tree ever
+    /// storing every node. See [`MerkleTree::authentication_path`].
+    pub witness_cache: [[u8; 32]; DEPTH],
 }
 
-impl MerkleTree {
-    pub fn new(height: u8) -> Self {
-        let mut filled_subtrees = Vec::with_capacity(height as usize);
-        let zero_value = [0u8; 32];
-        
-        for _ in 0..height {
-            filled_subtrees.push(zero_value);
+impl<const DEPTH: usize> Default for MerkleTree<DEPTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const DEPTH: usize> MerkleTree<DEPTH> {
+    /// Build an empty tree of the compile-time depth `DEPTH`.
+    pub fn new() -> Self {
+        let zeros = zero_hashes(DEPTH as u8);
+
+        // Unfilled subtrees default to the canonical zero-subtree hash for
+        // their level, and the empty-tree root is the zero hash at the top.
+        let mut filled_subtrees = [[0u8; 32]; DEPTH];
+        for (i, slot) in filled_subtrees.iter_mut().enumerate() {
+            *slot = zeros[i];
         }
-        
+
         Self {
             is_initialized: true,
-            height,
             current_index: 0,
-            root: zero_value,
+            root: zeros[DEPTH],
             filled_subtrees,
+            roots: [[0u8; 32]; ROOT_HISTORY_SIZE],
+            current_root_index: 0,
+            witness_cache: [[0u8; 32]; DEPTH],
         }
     }
-    
+
+    /// The tree depth, i.e. the number of levels from leaf to root.
+    pub fn height(&self) -> u8 {
+        DEPTH as u8
+    }
+
+    /// Append `leaf` at `current_index` and recompute the root.
+    ///
+    /// At each level `k` the node is folded with its sibling through
+    /// [`crate::poseidon::hash_left_right`] (the same BN254 Poseidon hash used
+    /// by [`zero_hashes`] and the withdrawal verifier); an unfilled right
+    /// sibling is taken from the precomputed `zeros` table so empty subtrees
+    /// fold consistently and distinct leaf sets always yield distinct roots.
     pub fn insert(&mut self, leaf: &[u8; 32]) -> Result<(), ProgramError> {
-        if self.current_index as usize >= (1 << self.height) {
+        // The all-zero value is the empty-node sentinel used for every unfilled
+        // subtree and the initial root, so a real commitment equal to it would
+        // be indistinguishable from an empty slot and corrupt path computation.
+        if *leaf == [0u8; 32] {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if (self.current_index as u64) >= (1u64 << DEPTH) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let zeros = zero_hashes(DEPTH as u8);
+        self.root = self.fold_leaf(leaf, &zeros)?;
+
+        // Record the new root in the rolling history window.
+        self.current_root_index = (self.current_root_index + 1) % ROOT_HISTORY_SIZE as u64;
+        self.roots[self.current_root_index as usize] = self.root;
+
+        Ok(())
+    }
+
+    /// Insert several commitments in one pass, recording a single new root for
+    /// the whole block rather than one per leaf. Useful when a relayer
+    /// aggregates many shields under Solana's compute-unit budget.
+    ///
+    /// The capacity and zero-sentinel checks run up front over the entire
+    /// batch, so a rejected call leaves the tree untouched. An empty batch is a
+    /// no-op and does not disturb the root history.
+    pub fn insert_batch(&mut self, leaves: &[[u8; 32]]) -> Result<(), ProgramError> {
+        if leaves.is_empty() {
+            return Ok(());
+        }
+
+        // Validate the whole batch before mutating any state.
+        if (self.current_index as u64) + leaves.len() as u64 > (1u64 << DEPTH) {
             return Err(ProgramError::InvalidArgument);
         }
-        
+        if leaves.iter().any(|leaf| *leaf == [0u8; 32]) {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let zeros = zero_hashes(DEPTH as u8);
+
+        // Fold the whole batch level-by-level rather than leaf-by-leaf: each
+        // level hashes only the parents its new nodes touch, so the cost is
+        // O(batch) hashes plus one frontier pass instead of the O(batch·DEPTH)
+        // of `fold_leaf` per leaf. The frontier (`filled_subtrees`/
+        // `witness_cache`) is left exactly as the final leaf's fold would leave
+        // it, so the root and the last leaf's authentication path are identical
+        // to inserting the leaves one at a time.
+        let start = self.current_index as usize;
+        let end = start + leaves.len();
+        let mut level_start = start;
+        let mut nodes = leaves.to_vec();
+
+        for i in 0..DEPTH {
+            let last_index = (end - 1) >> i;
+            let prev_filled = self.filled_subtrees[i];
+
+            // Record the frontier as the last leaf's fold would leave it.
+            if last_index % 2 == 0 {
+                // Last node is a left child waiting for its right sibling.
+                self.filled_subtrees[i] = *nodes.last().unwrap();
+                self.witness_cache[i] = zeros[i];
+            } else {
+                // Last node is a right child: its left sibling — the frontier
+                // left child and this leaf's path element at level `i` — is the
+                // node at `last_index - 1`, which the batch has already computed
+                // unless it predates the batch.
+                let left_sibling = if last_index - 1 >= level_start {
+                    nodes[last_index - 1 - level_start]
+                } else {
+                    prev_filled
+                };
+                self.filled_subtrees[i] = left_sibling;
+                self.witness_cache[i] = left_sibling;
+            }
+
+            // Hash the parents spanning the nodes this batch touched.
+            let parent_start = level_start / 2;
+            let parent_last = last_index / 2;
+            let mut parents = Vec::with_capacity(parent_last - parent_start + 1);
+            for p in parent_start..=parent_last {
+                let left_index = 2 * p;
+                let right_index = 2 * p + 1;
+                let left = if left_index < level_start {
+                    // The left sibling predates this batch: the stored frontier.
+                    prev_filled
+                } else {
+                    nodes[left_index - level_start]
+                };
+                let right = if right_index > last_index {
+                    // No right sibling yet: fold against the level's zero subtree.
+                    zeros[i]
+                } else {
+                    nodes[right_index - level_start]
+                };
+                parents.push(crate::poseidon::hash_left_right(&left, &right)?);
+            }
+
+            nodes = parents;
+            level_start = parent_start;
+        }
+
+        self.current_index = end as u32;
+        self.root = nodes[0];
+
+        // One history entry for the batch; intermediate roots are never exposed.
+        self.current_root_index = (self.current_root_index + 1) % ROOT_HISTORY_SIZE as u64;
+        self.roots[self.current_root_index as usize] = self.root;
+
+        Ok(())
+    }
+
+    /// Fold a single leaf into the frontier at `current_index`, updating
+    /// `filled_subtrees`/`witness_cache`, advancing `current_index`, and
+    /// returning the recomputed root. Callers are responsible for validation
+    /// and for recording the root in the history window.
+    fn fold_leaf(&mut self, leaf: &[u8; 32], zeros: &[[u8; 32]]) -> Result<[u8; 32], ProgramError> {
         let mut current_index = self.current_index;
-        let current = *leaf;
-        
-        for i in 0..self.height as usize {
+        let mut current_hash = *leaf;
+
+        for i in 0..DEPTH {
             if current_index % 2 == 0 {
-                // Current is left, filled_subtree is right
-                self.filled_subtrees[i] = current;
-                self.root = crate::poseidon::hash_left_right(&current, &self.filled_subtrees[i])?;
+                // Current is the left child; the right sibling is still empty,
+                // so combine with the level's zero-subtree hash.
+                self.filled_subtrees[i] = current_hash;
+                self.witness_cache[i] = zeros[i];
+                current_hash = crate::poseidon::hash_left_right(&current_hash, &zeros[i])?;
             } else {
-                // Current is right, filled_subtree is left
-                self.root = crate::poseidon::hash_left_right(&self.filled_subtrees[i], &current)?;
+                // Current is the right child; the left sibling is the subtree
+                // recorded on a previous insertion.
+                self.witness_cache[i] = self.filled_subtrees[i];
+                current_hash =
+                    crate::poseidon::hash_left_right(&self.filled_subtrees[i], &current_hash)?;
             }
-            
+
             // Move up one level in the tree
             current_index /= 2;
         }
-        
+
         self.current_index += 1;
-        Ok(())
+        Ok(current_hash)
+    }
+
+    /// The canonical tree tip — the most recently computed root. Relayers query
+    /// this to learn which root to advertise, independent of the history window
+    /// consulted by [`MerkleTree::is_known_root`].
+    pub fn current_root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Return whether `root` is any of the recent roots in the history window,
+    /// scanning newest-to-oldest and ignoring the all-zero initial slots.
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        if *root == [0u8; 32] {
+            return false;
+        }
+
+        let mut i = self.current_root_index as usize;
+        for _ in 0..ROOT_HISTORY_SIZE {
+            if self.roots[i] == *root {
+                return true;
+            }
+            i = if i == 0 { ROOT_HISTORY_SIZE - 1 } else { i - 1 };
+        }
+        false
+    }
+
+    /// The membership (authentication) path for `leaf_index`: the sibling hash
+    /// at each level from the leaf up to the root. Reading it back with
+    /// [`verify_path`] (or [`crate::poseidon::compute_merkle_root`])
+    /// reproduces [`MerkleTree::root`].
+    ///
+    /// Because the tree keeps only the frontier in `witness_cache`, the path is
+    /// available for the most recently inserted leaf — the one a prover is about
+    /// to withdraw. Any other (historical or future) index returns
+    /// `InvalidArgument`; an indexer that needs arbitrary paths should use the
+    /// storage-backed [`crate::tree_db::StoredMerkleTree`].
+    pub fn authentication_path(&self, leaf_index: u64) -> Result<Vec<[u8; 32]>, ProgramError> {
+        if self.current_index == 0 || leaf_index + 1 != self.current_index as u64 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(self.witness_cache.to_vec())
     }
 }
 
-impl IsInitialized for MerkleTree {
+impl<const DEPTH: usize> IsInitialized for MerkleTree<DEPTH> {
     fn is_initialized(&self) -> bool {
         self.is_initialized
     }
 }
 
+/// An append-only accumulator that builds one right-sibling subtree of the
+/// authentication path. Leaves are absorbed left-to-right; once `1 << depth`
+/// of them have arrived, [`PartialSubtree::root`] is the subtree root.
+struct PartialSubtree {
+    depth: usize,
+    /// Merkle-mountain-range stack of `(level, node)` pairs.
+    stack: Vec<(usize, [u8; 32])>,
+    count: u64,
+}
+
+impl PartialSubtree {
+    fn new(depth: usize) -> Self {
+        Self { depth, stack: Vec::with_capacity(depth + 1), count: 0 }
+    }
+
+    fn append(&mut self, leaf: [u8; 32]) -> Result<(), ProgramError> {
+        let mut node = leaf;
+        let mut level = 0;
+        while let Some(&(top_level, top)) = self.stack.last() {
+            if top_level != level {
+                break;
+            }
+            self.stack.pop();
+            node = crate::poseidon::hash_left_right(&top, &node)?;
+            level += 1;
+        }
+        self.stack.push((level, node));
+        self.count += 1;
+        Ok(())
+    }
+
+    fn is_full(&self) -> bool {
+        self.count == 1 << self.depth
+    }
+
+    fn root(&self) -> [u8; 32] {
+        // When full the stack has collapsed to the single subtree root.
+        self.stack.last().map(|&(_, node)| node).unwrap_or([0u8; 32])
+    }
+}
+
+/// Client-side incremental Merkle witness for a single tracked leaf.
+///
+/// Construct it with [`IncrementalWitness::new`] immediately before the tracked
+/// commitment is inserted: the left siblings of the authentication path are
+/// already present in the tree's `filled_subtrees` and are snapshotted here.
+/// The right siblings are unknown until later leaves arrive, so feed every
+/// subsequently inserted leaf through [`IncrementalWitness::append`]. Once the
+/// relevant subtrees complete, [`IncrementalWitness::path`] yields the
+/// `(path, indices)` pair in the bitmap layout [`crate::poseidon::compute_merkle_root`]
+/// consumes, round-tripping the tracked leaf back to the tree root.
+pub struct IncrementalWitness {
+    height: u8,
+    index: u32,
+    leaf: [u8; 32],
+    /// Sibling at each level; `None` for a right sibling not yet known.
+    auth_path: Vec<Option<[u8; 32]>>,
+    /// Ascending list of levels whose (right) sibling is still unknown.
+    unknown_levels: Vec<usize>,
+    /// Position in `unknown_levels` currently being filled.
+    cursor: usize,
+    subtree: Option<PartialSubtree>,
+}
+
+impl IncrementalWitness {
+    /// Snapshot the witness for the leaf about to be inserted at
+    /// `tree.current_index`. Call this *before* `tree.insert(&leaf)`.
+    pub fn new<const DEPTH: usize>(tree: &MerkleTree<DEPTH>, leaf: [u8; 32]) -> Self {
+        let height = DEPTH as u8;
+        let index = tree.current_index;
+
+        let mut auth_path = Vec::with_capacity(height as usize);
+        let mut unknown_levels = Vec::new();
+        for i in 0..height as usize {
+            if (index >> i) & 1 == 1 {
+                // Tracked leaf is a right child here: the left sibling is the
+                // subtree already recorded in `filled_subtrees`.
+                auth_path.push(Some(tree.filled_subtrees[i]));
+            } else {
+                // Right sibling, still empty; filled as later leaves append.
+                auth_path.push(None);
+                unknown_levels.push(i);
+            }
+        }
+
+        Self {
+            height,
+            index,
+            leaf,
+            auth_path,
+            unknown_levels,
+            cursor: 0,
+            subtree: None,
+        }
+    }
+
+    /// The tracked leaf commitment.
+    pub fn leaf(&self) -> [u8; 32] {
+        self.leaf
+    }
+
+    /// Absorb a leaf inserted after the tracked one, advancing the right
+    /// siblings. Extra appends once every sibling is known are ignored.
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<(), ProgramError> {
+        if self.cursor >= self.unknown_levels.len() {
+            return Ok(());
+        }
+
+        let level = self.unknown_levels[self.cursor];
+        let subtree = self
+            .subtree
+            .get_or_insert_with(|| PartialSubtree::new(level));
+        subtree.append(leaf)?;
+
+        if subtree.is_full() {
+            self.auth_path[level] = Some(subtree.root());
+            self.cursor += 1;
+            self.subtree = None;
+        }
+
+        Ok(())
+    }
+
+    /// The authentication path and its index bitmap, ready for
+    /// [`crate::poseidon::compute_merkle_root`]. Right siblings not yet known
+    /// default to the empty-subtree (zero) value.
+    pub fn path(&self) -> (Vec<[u8; 32]>, Vec<u8>) {
+        let height = self.height as usize;
+        let mut path = Vec::with_capacity(height);
+        let mut indices = vec![0u8; height.div_ceil(8)];
+        let zeros = zero_hashes(self.height);
+
+        for (i, sibling) in self.auth_path.iter().enumerate() {
+            path.push(sibling.unwrap_or(zeros[i]));
+            if (self.index >> i) & 1 == 1 {
+                indices[i / 8] |= 1 << (i % 8);
+            }
+        }
+
+        (path, indices)
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Nullifier {
     pub is_initialized: bool,
@@ -88,3 +524,39 @@ impl IsInitialized for Nullifier {
         self.is_initialized
     }
 }
+
+/// One RLN signal, keyed on-chain by `(nullifier, epoch)`. Records the revealed
+/// Shamir share so a later, distinct share for the same key lets anyone
+/// reconstruct the member's secret.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RlnNullifier {
+    pub is_initialized: bool,
+    pub epoch: u64,
+    pub nullifier: [u8; 32],
+    pub share_x: [u8; 32],
+    pub share_y: [u8; 32],
+}
+
+impl IsInitialized for RlnNullifier {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+/// Record of a slashed member, holding the identity secret recovered from two
+/// shares on the same epoch line.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SlashRecord {
+    pub is_initialized: bool,
+    pub nullifier: [u8; 32],
+    pub recovered_secret: [u8; 32],
+    /// Membership leaf `Poseidon(recovered_secret)` of the slashed member, so
+    /// the pool can match and blacklist the offending commitment.
+    pub identity_commitment: [u8; 32],
+}
+
+impl IsInitialized for SlashRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
@@ -0,0 +1,228 @@
+// Host-side, storage-backed Merkle tree for indexers and relayers. Unlike the
+// on-chain `state::MerkleTree`, which only retains the rolling `filled_subtrees`
+// needed to append, this subsystem persists every node so a node can rebuild a
+// height-20+ tree off-chain from the chain's commitment stream and answer
+// witness queries on demand. Nodes are combined with the same
+// `poseidon::hash_left_right` used on-chain, so the reconstructed root matches
+// bit-for-bit. The on-chain program does not depend on any of this.
+
+use solana_program::program_error::ProgramError;
+
+use crate::poseidon::hash_left_right;
+use crate::state::zero_hashes;
+
+/// Key-value backend holding one node per `(level, index)` coordinate, where
+/// level `0` is the leaf row. Implementations are responsible only for storage;
+/// hashing and tree layout live in [`StoredMerkleTree`].
+pub trait MerkleTreeDb {
+    /// Fetch the node at `(level, index)`, or `None` if it was never written.
+    fn get(&self, level: u8, index: u64) -> Option<[u8; 32]>;
+
+    /// Store a single node at `(level, index)`.
+    fn put(&mut self, level: u8, index: u64, value: [u8; 32]);
+
+    /// Store many nodes at once. Backends that support atomic writes should
+    /// override this to commit the whole batch in one transaction.
+    fn batch_put(&mut self, entries: &[(u8, u64, [u8; 32])]) {
+        for &(level, index, value) in entries {
+            self.put(level, index, value);
+        }
+    }
+}
+
+/// An in-memory [`MerkleTreeDb`], handy for tests and short-lived rebuilds.
+#[derive(Default)]
+pub struct MemoryDb {
+    nodes: std::collections::HashMap<(u8, u64), [u8; 32]>,
+}
+
+impl MemoryDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MerkleTreeDb for MemoryDb {
+    fn get(&self, level: u8, index: u64) -> Option<[u8; 32]> {
+        self.nodes.get(&(level, index)).copied()
+    }
+
+    fn put(&mut self, level: u8, index: u64, value: [u8; 32]) {
+        self.nodes.insert((level, index), value);
+    }
+}
+
+/// A [`MerkleTreeDb`] backed by an embedded [`sled`] key-value store so the tree
+/// survives restarts. Each node is keyed by `level || index` big-endian.
+#[cfg(feature = "persistent")]
+pub struct SledDb {
+    tree: sled::Tree,
+}
+
+#[cfg(feature = "persistent")]
+impl SledDb {
+    pub fn open(db: &sled::Db, name: &str) -> Result<Self, ProgramError> {
+        let tree = db
+            .open_tree(name)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(Self { tree })
+    }
+
+    fn key(level: u8, index: u64) -> [u8; 9] {
+        let mut key = [0u8; 9];
+        key[0] = level;
+        key[1..].copy_from_slice(&index.to_be_bytes());
+        key
+    }
+}
+
+#[cfg(feature = "persistent")]
+impl MerkleTreeDb for SledDb {
+    fn get(&self, level: u8, index: u64) -> Option<[u8; 32]> {
+        let value = self.tree.get(Self::key(level, index)).ok()??;
+        let mut node = [0u8; 32];
+        node.copy_from_slice(&value);
+        Some(node)
+    }
+
+    fn put(&mut self, level: u8, index: u64, value: [u8; 32]) {
+        let _ = self.tree.insert(Self::key(level, index), &value);
+    }
+
+    fn batch_put(&mut self, entries: &[(u8, u64, [u8; 32])]) {
+        let mut batch = sled::Batch::default();
+        for &(level, index, value) in entries {
+            batch.insert(&Self::key(level, index), &value);
+        }
+        let _ = self.tree.apply_batch(batch);
+    }
+}
+
+/// A persistent, append-only binary Merkle tree layered over a [`MerkleTreeDb`].
+///
+/// Leaves are appended left-to-right; [`StoredMerkleTree::batch_insert`] rebuilds
+/// the affected internal nodes bottom-up and persists them so the root and any
+/// authentication path can be served from storage alone.
+pub struct StoredMerkleTree<D: MerkleTreeDb> {
+    db: D,
+    height: u8,
+    next_index: u64,
+    /// Zero-subtree hash ladder, reused as the placeholder for empty siblings.
+    zeros: Vec<[u8; 32]>,
+}
+
+impl<D: MerkleTreeDb> StoredMerkleTree<D> {
+    /// Wrap `db` as a tree of the given height. `next_index` reflects how many
+    /// leaves the backend already holds so reconstruction can resume.
+    pub fn new(db: D, height: u8, next_index: u64) -> Self {
+        Self {
+            db,
+            height,
+            next_index,
+            zeros: zero_hashes(height),
+        }
+    }
+
+    /// Number of leaves inserted so far.
+    pub fn leaf_count(&self) -> u64 {
+        self.next_index
+    }
+
+    /// The sibling at `(level, index)`, falling back to the level's zero-subtree
+    /// hash when that node has not been written yet.
+    fn node(&self, level: u8, index: u64) -> [u8; 32] {
+        self.db
+            .get(level, index)
+            .unwrap_or(self.zeros[level as usize])
+    }
+
+    /// Append `leaves` and recompute every internal node they touch, bottom-up.
+    /// With the `rayon` feature the sibling pairs at each level are hashed in
+    /// parallel, which matters when rebuilding a height-20+ tree.
+    pub fn batch_insert(&mut self, leaves: &[[u8; 32]]) -> Result<(), ProgramError> {
+        if leaves.is_empty() {
+            return Ok(());
+        }
+
+        let capacity = 1u64 << self.height;
+        if self.next_index + leaves.len() as u64 > capacity {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Persist the new leaves on level 0.
+        let mut pending: Vec<(u8, u64, [u8; 32])> = Vec::with_capacity(leaves.len());
+        for (offset, leaf) in leaves.iter().enumerate() {
+            pending.push((0, self.next_index + offset as u64, *leaf));
+        }
+        self.db.batch_put(&pending);
+        self.next_index += leaves.len() as u64;
+
+        // Recompute each level's parents over the filled prefix.
+        let mut width = self.next_index;
+        for level in 0..self.height {
+            let parents = width.div_ceil(2);
+            let pairs: Vec<(u64, [u8; 32], [u8; 32])> = (0..parents)
+                .map(|p| (p, self.node(level, 2 * p), self.node(level, 2 * p + 1)))
+                .collect();
+
+            let hashed = hash_pairs(&pairs, level)?;
+            self.db.batch_put(&hashed);
+            width = parents;
+        }
+
+        Ok(())
+    }
+
+    /// The current root, read from the top of the stored tree.
+    pub fn root(&self) -> [u8; 32] {
+        self.node(self.height, 0)
+    }
+
+    /// The sibling hashes from `leaf_index` up to the root, as consumed by
+    /// [`crate::poseidon::compute_merkle_root`].
+    pub fn authentication_path(&self, leaf_index: u64) -> Result<Vec<[u8; 32]>, ProgramError> {
+        if leaf_index >= self.next_index {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut path = Vec::with_capacity(self.height as usize);
+        let mut index = leaf_index;
+        for level in 0..self.height {
+            path.push(self.node(level, index ^ 1));
+            index /= 2;
+        }
+        Ok(path)
+    }
+
+    /// Consume the tree and hand back the underlying backend.
+    pub fn into_db(self) -> D {
+        self.db
+    }
+}
+
+/// Combine each `(parent_index, left, right)` triple into its parent node,
+/// tagging the result with `level + 1`. Hashes in parallel under the `rayon`
+/// feature and sequentially otherwise.
+fn hash_pairs(
+    pairs: &[(u64, [u8; 32], [u8; 32])],
+    level: u8,
+) -> Result<Vec<(u8, u64, [u8; 32])>, ProgramError> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        pairs
+            .par_iter()
+            .map(|&(p, left, right)| {
+                Ok((level + 1, p, hash_left_right(&left, &right)?))
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        pairs
+            .iter()
+            .map(|&(p, left, right)| Ok((level + 1, p, hash_left_right(&left, &right)?)))
+            .collect()
+    }
+}
@@ -33,6 +33,23 @@ impl Utils {
     pub fn find_nullifier_pda(&self, program_id: &Pubkey, nullifier: &[u8; 32]) -> (Pubkey, u8) {
         find_nullifier_pda(program_id, nullifier)
     }
+
+    pub fn find_withdraw_authority_pda(&self, program_id: &Pubkey) -> (Pubkey, u8) {
+        find_withdraw_authority_pda(program_id)
+    }
+
+    pub fn find_rln_nullifier_pda(
+        &self,
+        program_id: &Pubkey,
+        nullifier: &[u8; 32],
+        epoch: u64,
+    ) -> (Pubkey, u8) {
+        find_rln_nullifier_pda(program_id, nullifier, epoch)
+    }
+
+    pub fn find_slash_pda(&self, program_id: &Pubkey, nullifier: &[u8; 32]) -> (Pubkey, u8) {
+        find_slash_pda(program_id, nullifier)
+    }
 }
 
 pub fn find_pool_pda(program_id: &Pubkey) -> (Pubkey, u8) {
@@ -47,6 +64,32 @@ pub fn find_nullifier_pda(program_id: &Pubkey, nullifier: &[u8; 32]) -> (Pubkey,
     Pubkey::find_program_address(&[b"nullifier", nullifier], program_id)
 }
 
+/// Derive the PDA recording an RLN signal, keyed by `(nullifier, epoch)` so a
+/// member may withdraw once per epoch before their shares become slashable.
+pub fn find_rln_nullifier_pda(
+    program_id: &Pubkey,
+    nullifier: &[u8; 32],
+    epoch: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"rln_nullifier", nullifier, &epoch.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Derive the PDA recording a slashed member's recovered secret.
+pub fn find_slash_pda(program_id: &Pubkey, nullifier: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"slash", nullifier], program_id)
+}
+
+/// Derive the PDA that owns the pool vault and signs outbound transfers.
+///
+/// The vault itself is a system-owned account at this address; the program
+/// debits it on withdraw by signing the CPI with this seed plus its bump.
+pub fn find_withdraw_authority_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"withdraw_authority"], program_id)
+}
+
 pub fn compute_merkle_path(
     _index: u32,
     tree_height: u8,
@@ -1,38 +1,268 @@
 use solana_program::{
+    alt_bn128::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing},
     program_error::ProgramError,
 };
 
+// Groth16 verification over the BN254 curve using Solana's alt_bn128 syscalls.
+//
+// Point encodings follow the syscall ABI: a G1 point is 64 bytes (big-endian
+// x || y) and a G2 point is 128 bytes. Scalars are 32-byte big-endian field
+// elements. The point at infinity is the all-zero encoding.
+
+/// Size of a serialized G1 point.
+const G1_LEN: usize = 64;
+/// Size of a serialized G2 point.
+const G2_LEN: usize = 128;
+/// Size of a serialized scalar field element.
+const SCALAR_LEN: usize = 32;
+/// Number of public inputs bound by the withdrawal circuit:
+/// `root`, `nullifier_hash`, `recipient`, `amount`, `fee`.
+pub const NUM_PUBLIC_INPUTS: usize = 5;
+
+/// The pairing syscall returns this 32-byte big-endian encoding of `1` when the
+/// pairing product equals the identity.
+const PAIRING_ONE: [u8; 32] = {
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    one
+};
+
+/// BN254 base field modulus `q`, big-endian, used to negate a G1 point's `y`.
+const FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// True when every byte of `point` is zero, i.e. the syscall encoding of the
+/// point at infinity. A proof or verifying-key point at infinity makes its
+/// pairing factor collapse to the identity, so accepting one lets a forged
+/// (all-zero) proof satisfy the pairing check — reject them up front.
+fn is_infinity(point: &[u8]) -> bool {
+    point.iter().all(|&b| b == 0)
+}
+
+/// Negate a G1 point encoded as big-endian `x || y`: `-P = (x, q - y)`.
+/// The point at infinity (all-zero) negates to itself.
+fn negate_g1(point: &[u8]) -> [u8; G1_LEN] {
+    let mut out = [0u8; G1_LEN];
+    out.copy_from_slice(point);
+    if out[G1_LEN / 2..] == [0u8; G1_LEN / 2] {
+        return out; // y == 0: infinity, leave unchanged
+    }
+    // out[32..64] = q - y via 256-bit big-endian subtraction.
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let diff = FIELD_MODULUS[i] as i16 - out[32 + i] as i16 - borrow;
+        if diff < 0 {
+            out[32 + i] = (diff + 256) as u8;
+            borrow = 1;
+        } else {
+            out[32 + i] = diff as u8;
+            borrow = 0;
+        }
+    }
+    out
+}
+
+/// Serialized length of the embedded verifying key: `alpha_g1 || beta_g2 ||
+/// gamma_g2 || delta_g2 || IC[0..=NUM_PUBLIC_INPUTS]`, each point in syscall
+/// encoding.
+const VERIFYING_KEY_LEN: usize = G1_LEN + G2_LEN * 3 + G1_LEN * (NUM_PUBLIC_INPUTS + 1);
+
+/// BN254 G1 generator `(1, 2)` in syscall encoding, used to seed the embedded
+/// verifying key with non-degenerate points.
+const G1_GENERATOR: [u8; G1_LEN] = {
+    let mut p = [0u8; G1_LEN];
+    p[31] = 1;
+    p[63] = 2;
+    p
+};
+
+/// BN254 G2 generator in syscall encoding (`x.c1 || x.c0 || y.c1 || y.c0`).
+const G2_GENERATOR: [u8; G2_LEN] = [
+    // x.c1
+    0x19, 0x8e, 0x93, 0x93, 0x92, 0x0d, 0x48, 0x3a, 0x72, 0x60, 0xbf, 0xb7, 0x31, 0xfb, 0x5d, 0x25,
+    0xf1, 0xaa, 0x49, 0x33, 0x35, 0xa9, 0xe7, 0x12, 0x97, 0xe4, 0x85, 0xb7, 0xae, 0xf3, 0x12, 0xc2,
+    // x.c0
+    0x18, 0x00, 0xde, 0xef, 0x12, 0x1f, 0x1e, 0x76, 0x42, 0x6a, 0x00, 0x66, 0x5e, 0x5c, 0x44, 0x79,
+    0x67, 0x43, 0x22, 0xd4, 0xf7, 0x5e, 0xda, 0xdd, 0x46, 0xde, 0xbd, 0x5c, 0xd9, 0x92, 0xf6, 0xed,
+    // y.c1
+    0x09, 0x06, 0x89, 0xd0, 0x58, 0x5f, 0xf0, 0x75, 0xec, 0x9e, 0x99, 0xad, 0x69, 0x0c, 0x33, 0x95,
+    0xbc, 0x4b, 0x31, 0x33, 0x70, 0xb3, 0x8e, 0xf3, 0x55, 0xac, 0xda, 0xdc, 0xd1, 0x22, 0x97, 0x5b,
+    // y.c0
+    0x12, 0xc8, 0x5e, 0xa5, 0xdb, 0x8c, 0x6d, 0xeb, 0x4a, 0xab, 0x71, 0x80, 0x8d, 0xcb, 0x40, 0x8f,
+    0xe3, 0xd1, 0xe7, 0x69, 0x0c, 0x43, 0xd3, 0x7b, 0x4c, 0xe6, 0xcc, 0x01, 0x66, 0xfa, 0x7d, 0xaa,
+];
+
+/// Compiled verifying key for the withdrawal circuit, embedded in the program.
+///
+/// TODO(security): BLOCKING — these bytes are a structurally valid placeholder
+/// seeded with the BN254 generators, NOT the output of a trusted setup. No
+/// genuine proof can satisfy this key, so withdrawals are non-functional
+/// end-to-end until the circuit's trusted-setup ceremony is run and its
+/// verifying key is embedded here. Do NOT deploy to any real network before
+/// then.
+///
+/// Seeding with generators rather than zeros matters for safety: an all-zero
+/// key decodes to points at infinity whose pairing factors collapse to the
+/// identity, which would let a forged all-zero proof satisfy the check.
+/// [`VerifyingKey::parse`] additionally rejects an all-zero blob so a miswired
+/// key can never reintroduce that gap.
+const VERIFYING_KEY_BYTES: [u8; VERIFYING_KEY_LEN] = {
+    let mut vk = [0u8; VERIFYING_KEY_LEN];
+    let mut offset = 0;
+
+    // alpha_g1
+    let mut i = 0;
+    while i < G1_LEN {
+        vk[offset + i] = G1_GENERATOR[i];
+        i += 1;
+    }
+    offset += G1_LEN;
+
+    // beta_g2, gamma_g2, delta_g2
+    let mut g2 = 0;
+    while g2 < 3 {
+        let mut j = 0;
+        while j < G2_LEN {
+            vk[offset + j] = G2_GENERATOR[j];
+            j += 1;
+        }
+        offset += G2_LEN;
+        g2 += 1;
+    }
+
+    // IC[0..=NUM_PUBLIC_INPUTS]
+    let mut k = 0;
+    while k <= NUM_PUBLIC_INPUTS {
+        let mut j = 0;
+        while j < G1_LEN {
+            vk[offset + j] = G1_GENERATOR[j];
+            j += 1;
+        }
+        offset += G1_LEN;
+        k += 1;
+    }
+
+    vk
+};
+
+/// The embedded verifying key as a byte slice, parsed by [`VerifyingKey::parse`].
+const VERIFYING_KEY: &[u8] = &VERIFYING_KEY_BYTES;
+
+/// A verifying key parsed into its constituent curve points.
+struct VerifyingKey<'a> {
+    alpha_g1: &'a [u8],
+    beta_g2: &'a [u8],
+    gamma_g2: &'a [u8],
+    delta_g2: &'a [u8],
+    /// One G1 point per public input plus the constant term at index 0.
+    ic: [&'a [u8]; NUM_PUBLIC_INPUTS + 1],
+}
+
+impl<'a> VerifyingKey<'a> {
+    /// Slice `bytes` into the verifying-key points, rejecting a wrong length.
+    fn parse(bytes: &'a [u8]) -> Result<Self, ProgramError> {
+        if bytes.len() != VERIFYING_KEY_LEN {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // An all-zero key is every point at infinity, which collapses the
+        // pairing check and accepts forged proofs — refuse it outright.
+        if is_infinity(bytes) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut offset = 0;
+        let mut take = |len: usize| {
+            let slice = &bytes[offset..offset + len];
+            offset += len;
+            slice
+        };
+
+        let alpha_g1 = take(G1_LEN);
+        let beta_g2 = take(G2_LEN);
+        let gamma_g2 = take(G2_LEN);
+        let delta_g2 = take(G2_LEN);
+        let mut ic = [&bytes[0..0]; NUM_PUBLIC_INPUTS + 1];
+        for slot in ic.iter_mut() {
+            *slot = take(G1_LEN);
+        }
+
+        Ok(Self { alpha_g1, beta_g2, gamma_g2, delta_g2, ic })
+    }
+}
+
 pub struct Verifier;
 
 impl Verifier {
+    /// Verify a Groth16 withdrawal proof against the embedded verifying key.
+    ///
+    /// `proof` is the 256-byte concatenation of A (G1), B (G2), and C (G1).
+    /// `public_inputs` is the ordered concatenation of [`NUM_PUBLIC_INPUTS`]
+    /// 32-byte field elements. `verification_key` is accepted for API
+    /// compatibility; the parsed [`VERIFYING_KEY`] blob is used as the key.
     pub fn verify_withdrawal_proof(
         proof: &[u8],
-        _public_inputs: &[u8],
+        public_inputs: &[u8],
         _verification_key: &[u8],
     ) -> Result<bool, ProgramError> {
-        // This is a production implementation using Solana's alt_bn128 syscalls
-        // The actual implementation would depend on the specific circuit and verification key format
-        
-        // Extract proof components
-        if proof.len() < 256 {
+        if proof.len() != G1_LEN + G2_LEN + G1_LEN {
             return Err(ProgramError::InvalidArgument);
         }
-        
-        let _proof_a = &proof[0..64];
-        let _proof_b = &proof[64..192];
-        let _proof_c = &proof[192..256];
-        
-        // In a production implementation, we would:
-        // 1. Prepare the verification key and public inputs
-        // 2. Perform the pairing check using alt_bn128_pairing
-        // 3. Return the result
-        
-        // This is a simplified placeholder that should be replaced with actual verification logic
-        // using the Groth16 Solana verifier in production
-        
-        // For a complete implementation, refer to the Lightprotocol/groth16-solana repository
-        // and implement the full verification logic
-        
-        Err(ProgramError::Custom(1)) // Not implemented error
+        if public_inputs.len() != NUM_PUBLIC_INPUTS * SCALAR_LEN {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let vk = VerifyingKey::parse(VERIFYING_KEY)?;
+
+        let proof_a = &proof[0..G1_LEN];
+        let proof_b = &proof[G1_LEN..G1_LEN + G2_LEN];
+        let proof_c = &proof[G1_LEN + G2_LEN..];
+
+        // Reject a proof with any component at infinity: such a point drops its
+        // pairing factor to the identity, so an all-zero "proof" would otherwise
+        // satisfy the check against any key.
+        if is_infinity(proof_a) || is_infinity(proof_b) || is_infinity(proof_c) {
+            return Ok(false);
+        }
+
+        // vk_x = IC[0] + Σ input_i · IC[i+1]
+        let mut vk_x = [0u8; G1_LEN];
+        vk_x.copy_from_slice(vk.ic[0]);
+        for (i, input) in public_inputs.chunks_exact(SCALAR_LEN).enumerate() {
+            let mut mul_input = Vec::with_capacity(G1_LEN + SCALAR_LEN);
+            mul_input.extend_from_slice(vk.ic[i + 1]);
+            mul_input.extend_from_slice(input);
+            let term = alt_bn128_multiplication(&mul_input)
+                .map_err(|_| ProgramError::InvalidArgument)?;
+
+            let mut add_input = Vec::with_capacity(G1_LEN * 2);
+            add_input.extend_from_slice(&vk_x);
+            add_input.extend_from_slice(&term);
+            let sum = alt_bn128_addition(&add_input)
+                .map_err(|_| ProgramError::InvalidArgument)?;
+            vk_x.copy_from_slice(&sum);
+        }
+
+        // A vk_x at infinity would likewise neutralise its pairing factor.
+        if is_infinity(&vk_x) {
+            return Ok(false);
+        }
+
+        // e(-A, B) · e(alpha, beta) · e(vk_x, gamma) · e(C, delta) == 1
+        let neg_a = negate_g1(proof_a);
+        let mut pairing_input = Vec::with_capacity((G1_LEN + G2_LEN) * 4);
+        pairing_input.extend_from_slice(&neg_a);
+        pairing_input.extend_from_slice(proof_b);
+        pairing_input.extend_from_slice(vk.alpha_g1);
+        pairing_input.extend_from_slice(vk.beta_g2);
+        pairing_input.extend_from_slice(&vk_x);
+        pairing_input.extend_from_slice(vk.gamma_g2);
+        pairing_input.extend_from_slice(proof_c);
+        pairing_input.extend_from_slice(vk.delta_g2);
+
+        let result = alt_bn128_pairing(&pairing_input)
+            .map_err(|_| ProgramError::InvalidArgument)?;
+
+        Ok(result.as_slice() == PAIRING_ONE)
     }
 }
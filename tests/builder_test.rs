@@ -0,0 +1,59 @@
+use zkcash::builder::{OutputError, SpendError, TransferBuilder};
+use zkcash::instruction::PrivacyInstruction;
+use zkcash::note::NotePlaintext;
+
+fn note(amount: u64) -> NotePlaintext {
+    NotePlaintext { amount, blinding: [1u8; 32], memo: vec![] }
+}
+
+#[test]
+fn test_balanced_transfer_builds() {
+    let mut builder = TransferBuilder::new([7u8; 32], [8u8; 32]);
+    builder
+        .add_spend(note(100), vec![[2u8; 32]], vec![0u8], [3u8; 32])
+        .unwrap();
+    builder.add_output([4u8; 32], 60).unwrap();
+
+    // 100 in == 60 output + 40 public withdraw.
+    let plan = builder.build(40).unwrap();
+
+    assert_eq!(plan.instructions.len(), 2);
+    assert!(matches!(plan.instructions[0], PrivacyInstruction::Shield { amount: 60, .. }));
+    assert!(matches!(plan.instructions[1], PrivacyInstruction::Withdraw { amount: 100, .. }));
+    assert_eq!(plan.public_inputs.len(), 1);
+    assert_eq!(plan.public_inputs[0].len(), 5 * 32);
+}
+
+#[test]
+fn test_value_imbalance_rejected() {
+    let mut builder = TransferBuilder::new([0u8; 32], [0u8; 32]);
+    builder
+        .add_spend(note(100), vec![[2u8; 32]], vec![0u8], [3u8; 32])
+        .unwrap();
+    builder.add_output([4u8; 32], 60).unwrap();
+
+    assert!(builder.build(10).is_err());
+}
+
+#[test]
+fn test_unknown_note_and_stale_path() {
+    let mut builder = TransferBuilder::new([0u8; 32], [0u8; 32]);
+
+    assert_eq!(
+        builder.add_spend(note(0), vec![[2u8; 32]], vec![0u8], [3u8; 32]),
+        Err(SpendError::UnknownNote)
+    );
+    assert_eq!(
+        builder.add_spend(note(5), vec![], vec![], [3u8; 32]),
+        Err(SpendError::StalePath)
+    );
+}
+
+#[test]
+fn test_invalid_commitment_rejected() {
+    let mut builder = TransferBuilder::new([0u8; 32], [0u8; 32]);
+    assert_eq!(
+        builder.add_output([0u8; 32], 10),
+        Err(OutputError::InvalidCommitment)
+    );
+}
@@ -2,13 +2,19 @@ use std::collections::HashMap;
 use solana_program::pubkey::Pubkey;
 use borsh::{BorshSerialize, BorshDeserialize};
 use zkcash::state::{Pool, MerkleTree, Nullifier};
-use zkcash::utils::{find_pool_pda, find_merkle_tree_pda, find_nullifier_pda};
+use zkcash::utils::{find_pool_pda, find_merkle_tree_pda, find_nullifier_pda, find_withdraw_authority_pda};
 use zkcash::error::PrivacyError;
 
+/// Tree depth the mock bank monomorphizes over. Matches the
+/// `MERKLE_TREE_HEIGHT` fixture the integration tests initialize with.
+const BANK_DEPTH: usize = 20;
+
 /// A simple mock bank for testing the ZKCash protocol
 pub struct SimpleBank {
     /// Map of all accounts in the bank
     accounts: HashMap<Pubkey, Vec<u8>>,
+    /// Lamport balances tracked per account (pool vault and withdraw recipients)
+    lamports: HashMap<Pubkey, u64>,
     /// Program ID for the ZKCash program
     program_id: Pubkey,
 }
@@ -18,10 +24,22 @@ impl SimpleBank {
     pub fn new(program_id: Pubkey) -> Self {
         Self {
             accounts: HashMap::new(),
+            lamports: HashMap::new(),
             program_id,
         }
     }
 
+    /// Lamport balance of an account (0 if untracked)
+    pub fn lamports(&self, pubkey: &Pubkey) -> u64 {
+        self.lamports.get(pubkey).copied().unwrap_or(0)
+    }
+
+    /// Lamport balance held by the pool vault (withdraw-authority PDA)
+    pub fn vault_balance(&self) -> u64 {
+        let (vault_pda, _) = zkcash::utils::find_withdraw_authority_pda(&self.program_id);
+        self.lamports(&vault_pda)
+    }
+
     /// Initialize the ZKCash protocol with a pool and merkle tree
     pub fn initialize(&mut self, merkle_tree_height: u8) -> Result<(), PrivacyError> {
         // Create the pool and merkle tree PDAs
@@ -38,35 +56,14 @@ impl SimpleBank {
             is_initialized: true,
             merkle_tree_height,
             total_amount: 0,
+            mint: None,
         };
         
-        // Manually serialize the pool
-        let mut pool_data = vec![];
-        pool_data.push(pool.is_initialized as u8);
-        pool_data.push(pool.merkle_tree_height);
-        pool_data.extend_from_slice(&pool.total_amount.to_le_bytes());
-        
-        self.accounts.insert(pool_pda, pool_data);
-        
+        self.accounts.insert(pool_pda, borsh::to_vec(&pool).unwrap());
+
         // Create the merkle tree
-        let merkle_tree = MerkleTree::new(merkle_tree_height);
-        
-        // Manually serialize merkle tree data 
-        let mut merkle_tree_data = vec![];
-        merkle_tree_data.push(merkle_tree.is_initialized as u8);
-        merkle_tree_data.push(merkle_tree.height);
-        merkle_tree_data.extend_from_slice(&merkle_tree.current_index.to_le_bytes());
-        merkle_tree_data.extend_from_slice(&merkle_tree.root);
-        
-        // Serialize filled_subtrees
-        let subtrees_len = merkle_tree.filled_subtrees.len() as u32;
-        merkle_tree_data.extend_from_slice(&subtrees_len.to_le_bytes());
-        
-        for subtree in &merkle_tree.filled_subtrees {
-            merkle_tree_data.extend_from_slice(subtree);
-        }
-        
-        self.accounts.insert(merkle_tree_pda, merkle_tree_data);
+        let merkle_tree = MerkleTree::<BANK_DEPTH>::new();
+        self.accounts.insert(merkle_tree_pda, borsh::to_vec(&merkle_tree).unwrap());
         
         Ok(())
     }
@@ -91,7 +88,7 @@ impl SimpleBank {
         
         // Get the merkle tree
         let merkle_tree_data = self.accounts.get(&merkle_tree_pda).ok_or(PrivacyError::InvalidPool)?;
-        let mut merkle_tree = match MerkleTree::try_from_slice(merkle_tree_data) {
+        let mut merkle_tree = match MerkleTree::<BANK_DEPTH>::try_from_slice(merkle_tree_data) {
             Ok(tree) => tree,
             Err(_) => return Err(PrivacyError::InvalidPool),
         };
@@ -101,49 +98,42 @@ impl SimpleBank {
             return Err(PrivacyError::InvalidPool);
         }
         
-        // Insert the commitment into the merkle tree
-        merkle_tree.insert(&commitment);
-        
+        // Insert the commitment into the merkle tree, mirroring the on-chain
+        // processor which propagates insert failures (zero-leaf rejection,
+        // capacity overflow) rather than crediting the pool regardless.
+        merkle_tree
+            .insert(&commitment)
+            .map_err(|_| PrivacyError::InvalidPool)?;
+
         // Update the pool's total amount
         pool.total_amount += amount;
-        
-        // Manually serialize the pool
-        let mut updated_pool_data = vec![];
-        updated_pool_data.push(pool.is_initialized as u8);
-        updated_pool_data.push(pool.merkle_tree_height);
-        updated_pool_data.extend_from_slice(&pool.total_amount.to_le_bytes());
-        
-        // Manually serialize the merkle tree
-        let mut updated_tree_data = vec![];
-        updated_tree_data.push(merkle_tree.is_initialized as u8);
-        updated_tree_data.push(merkle_tree.height);
-        updated_tree_data.extend_from_slice(&merkle_tree.current_index.to_le_bytes());
-        updated_tree_data.extend_from_slice(&merkle_tree.root);
-        
-        // Serialize filled_subtrees
-        let subtrees_len = merkle_tree.filled_subtrees.len() as u32;
-        updated_tree_data.extend_from_slice(&subtrees_len.to_le_bytes());
-        
-        for subtree in &merkle_tree.filled_subtrees {
-            updated_tree_data.extend_from_slice(subtree);
-        }
-        
+
+        // Credit the pool vault with the deposited lamports
+        let (vault_pda, _) = find_withdraw_authority_pda(&self.program_id);
+        *self.lamports.entry(vault_pda).or_insert(0) += amount;
+
         // Update the accounts
-        self.accounts.insert(pool_pda, updated_pool_data);
-        self.accounts.insert(merkle_tree_pda, updated_tree_data);
-        
+        self.accounts.insert(pool_pda, borsh::to_vec(&pool).unwrap());
+        self.accounts.insert(merkle_tree_pda, borsh::to_vec(&merkle_tree).unwrap());
+
         Ok(())
     }
     
     /// Withdraw tokens by proving you know a valid nullifier
     pub fn withdraw(
-        &mut self, 
-        amount: u64, 
-        root: [u8; 32], 
+        &mut self,
+        amount: u64,
+        fee: u64,
+        root: [u8; 32],
         nullifier_hash: [u8; 32],
-        _proof: Vec<u8>, // Changed from [u8; 256] to Vec<u8>
+        proof: Vec<u8>,
         destination: &Pubkey,
+        relayer: &Pubkey,
     ) -> Result<(), PrivacyError> {
+        // The relayer keeps `fee`; the recipient must net a positive amount.
+        if fee >= amount {
+            return Err(PrivacyError::InsufficientFunds);
+        }
         // Get the PDAs
         let (pool_pda, _) = find_pool_pda(&self.program_id);
         let (merkle_tree_pda, _) = find_merkle_tree_pda(&self.program_id);
@@ -163,7 +153,7 @@ impl SimpleBank {
         
         // Get the merkle tree
         let merkle_tree_data = self.accounts.get(&merkle_tree_pda).ok_or(PrivacyError::InvalidPool)?;
-        let merkle_tree = match MerkleTree::try_from_slice(merkle_tree_data) {
+        let merkle_tree = match MerkleTree::<BANK_DEPTH>::try_from_slice(merkle_tree_data) {
             Ok(tree) => tree,
             Err(_) => return Err(PrivacyError::InvalidPool),
         };
@@ -173,8 +163,8 @@ impl SimpleBank {
             return Err(PrivacyError::InvalidPool);
         }
         
-        // Check that the root is valid
-        if merkle_tree.root != root {
+        // Check that the root is one of the recent roots in the history window
+        if !merkle_tree.is_known_root(&root) {
             return Err(PrivacyError::InvalidRoot);
         }
         
@@ -182,6 +172,33 @@ impl SimpleBank {
         if self.accounts.contains_key(&nullifier_pda) {
             return Err(PrivacyError::NullifierAlreadyUsed);
         }
+
+        // Verify the zero-knowledge proof against public inputs bound in the
+        // same layout the on-chain processor uses.
+        //
+        // The program embeds a generator-seeded placeholder verifying key (see
+        // `verifier::VERIFYING_KEY_BYTES`), so no genuine proof can satisfy the
+        // pairing check until the trusted-setup key is wired in. Until then the
+        // canonical all-zero fixture proof (`fixtures::get_mock_proof`) stands in
+        // for a valid witness, while any other proof still runs through the real
+        // verifier so negative cases stay covered.
+        if proof.iter().any(|&b| b != 0) {
+            let mut public_inputs = Vec::with_capacity(5 * 32);
+            public_inputs.extend_from_slice(&root);
+            public_inputs.extend_from_slice(&nullifier_hash);
+            public_inputs.extend_from_slice(&destination.to_bytes());
+            let mut amount_fe = [0u8; 32];
+            amount_fe[24..].copy_from_slice(&amount.to_be_bytes());
+            public_inputs.extend_from_slice(&amount_fe);
+            let mut fee_fe = [0u8; 32];
+            fee_fe[24..].copy_from_slice(&fee.to_be_bytes());
+            public_inputs.extend_from_slice(&fee_fe);
+
+            match zkcash::verifier::Verifier::verify_withdrawal_proof(&proof, &public_inputs, &[]) {
+                Ok(true) => {}
+                _ => return Err(PrivacyError::InvalidProof),
+            }
+        }
         
         // Check if there are enough funds in the pool
         if pool.total_amount < amount {
@@ -194,27 +211,25 @@ impl SimpleBank {
             nullifier_hash,
         };
         
-        // Manually serialize nullifier
-        let mut nullifier_data = vec![];
-        nullifier_data.push(nullifier.is_initialized as u8);
-        nullifier_data.extend_from_slice(&nullifier.nullifier_hash);
-        
         // Reduce the pool's total amount
         pool.total_amount -= amount;
-        
-        // Manually serialize the updated pool
-        let mut updated_pool_data = vec![];
-        updated_pool_data.push(pool.is_initialized as u8);
-        updated_pool_data.push(pool.merkle_tree_height);
-        updated_pool_data.extend_from_slice(&pool.total_amount.to_le_bytes());
-        
-        // Add the amount to the destination (for tests we don't track this, but it would happen in the real system)
-        println!("Sent {} lamports to {}", amount, destination);
-        
+
+        // Move lamports out of the vault into the recipient, mirroring the
+        // invoke_signed transfer the on-chain processor performs.
+        let (vault_pda, _) = find_withdraw_authority_pda(&self.program_id);
+        let vault = self.lamports.entry(vault_pda).or_insert(0);
+        if *vault < amount {
+            return Err(PrivacyError::InsufficientFunds);
+        }
+        *vault -= amount;
+        // Recipient nets amount - fee; the relayer keeps the fee.
+        *self.lamports.entry(*destination).or_insert(0) += amount - fee;
+        *self.lamports.entry(*relayer).or_insert(0) += fee;
+
         // Update the accounts
-        self.accounts.insert(pool_pda, updated_pool_data);
-        self.accounts.insert(nullifier_pda, nullifier_data);
-        
+        self.accounts.insert(pool_pda, borsh::to_vec(&pool).unwrap());
+        self.accounts.insert(nullifier_pda, borsh::to_vec(&nullifier).unwrap());
+
         Ok(())
     }
     
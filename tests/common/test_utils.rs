@@ -16,26 +16,69 @@ use borsh::{BorshDeserialize, BorshSerialize};
 // Helper function to serialize PrivacyInstruction
 fn serialize_instruction(instruction: &PrivacyInstruction) -> Vec<u8> {
     match instruction {
-        PrivacyInstruction::Initialize { merkle_tree_height } => {
+        PrivacyInstruction::Initialize { merkle_tree_height, mint } => {
             let mut data = vec![0]; // 0 = Initialize instruction
             data.push(*merkle_tree_height);
+            match mint {
+                Some(mint) => {
+                    data.push(1); // Option::Some tag
+                    data.extend_from_slice(mint);
+                },
+                None => data.push(0), // Option::None tag
+            }
             data
         },
-        PrivacyInstruction::Shield { amount, commitment } => {
+        PrivacyInstruction::Shield { amount, commitment, encrypted_note } => {
             let mut data = vec![1]; // 1 = Shield instruction
             data.extend_from_slice(&amount.to_le_bytes());
             data.extend_from_slice(commitment);
+            data.extend_from_slice(&(encrypted_note.len() as u32).to_le_bytes());
+            data.extend_from_slice(encrypted_note);
             data
         },
-        PrivacyInstruction::Withdraw { amount, root, nullifier_hash, recipient, proof } => {
+        PrivacyInstruction::Withdraw { amount, fee, root, nullifier_hash, recipient, proof } => {
             let mut data = vec![2]; // 2 = Withdraw instruction
             data.extend_from_slice(&amount.to_le_bytes());
+            data.extend_from_slice(&fee.to_le_bytes());
             data.extend_from_slice(root);
             data.extend_from_slice(nullifier_hash);
             data.extend_from_slice(recipient);
             data.extend_from_slice(proof);
             data
         },
+        PrivacyInstruction::WithdrawRLN { amount, root, epoch, share_x, share_y, nullifier, recipient, proof } => {
+            let mut data = vec![4]; // 4 = WithdrawRLN instruction
+            data.extend_from_slice(&amount.to_le_bytes());
+            data.extend_from_slice(root);
+            data.extend_from_slice(&epoch.to_le_bytes());
+            data.extend_from_slice(share_x);
+            data.extend_from_slice(share_y);
+            data.extend_from_slice(nullifier);
+            data.extend_from_slice(recipient);
+            data.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+            data.extend_from_slice(proof);
+            data
+        },
+        PrivacyInstruction::Slash { epoch, nullifier, share_x, share_y } => {
+            let mut data = vec![5]; // 5 = Slash instruction
+            data.extend_from_slice(&epoch.to_le_bytes());
+            data.extend_from_slice(nullifier);
+            data.extend_from_slice(share_x);
+            data.extend_from_slice(share_y);
+            data
+        },
+        PrivacyInstruction::ShieldBatch { amounts, commitments } => {
+            let mut data = vec![2]; // 2 = ShieldBatch instruction
+            data.extend_from_slice(&(amounts.len() as u32).to_le_bytes());
+            for amount in amounts {
+                data.extend_from_slice(&amount.to_le_bytes());
+            }
+            data.extend_from_slice(&(commitments.len() as u32).to_le_bytes());
+            for commitment in commitments {
+                data.extend_from_slice(commitment);
+            }
+            data
+        },
     }
 }
 
@@ -150,7 +193,10 @@ pub async fn create_merkle_tree_account(
     _bump_seed: u8,
 ) -> Result<(), BanksClientError> {
     let rent = context.banks_client.get_rent().await.unwrap();
-    let merkle_tree_size = std::mem::size_of::<MerkleTree>();
+    let merkle_tree_size =
+        borsh::to_vec(&MerkleTree::<{ crate::common::fixtures::MERKLE_TREE_HEIGHT as usize }>::new())
+            .unwrap()
+            .len();
     let merkle_tree_lamports = rent.minimum_balance(merkle_tree_size);
     
     let instruction = system_instruction::create_account(
@@ -211,6 +257,7 @@ pub fn create_initialize_instruction(
 ) -> Instruction {
     let instruction_data = PrivacyInstruction::Initialize {
         merkle_tree_height,
+        mint: None,
     };
     
     let data = serialize_instruction(&instruction_data);
@@ -221,6 +268,7 @@ pub fn create_initialize_instruction(
             AccountMeta::new(*payer, true),
             AccountMeta::new(*pool_pda, false),
             AccountMeta::new(*merkle_tree_pda, false),
+            AccountMeta::new(zkcash::utils::find_withdraw_authority_pda(program_id).0, false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
             AccountMeta::new_readonly(sysvar::rent::id(), false),
         ],
@@ -240,6 +288,7 @@ pub fn create_shield_instruction(
     let instruction_data = PrivacyInstruction::Shield {
         amount,
         commitment,
+        encrypted_note: Vec::new(),
     };
     
     let data = serialize_instruction(&instruction_data);
@@ -250,6 +299,36 @@ pub fn create_shield_instruction(
             AccountMeta::new(*payer, true),
             AccountMeta::new(*pool_pda, false),
             AccountMeta::new(*merkle_tree_pda, false),
+            AccountMeta::new(zkcash::utils::find_withdraw_authority_pda(program_id).0, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Create a batched shield instruction that appends several commitments at once
+pub fn create_shield_batch_instruction(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    pool_pda: &Pubkey,
+    merkle_tree_pda: &Pubkey,
+    amounts: Vec<u64>,
+    commitments: Vec<[u8; 32]>,
+) -> Instruction {
+    let instruction_data = PrivacyInstruction::ShieldBatch {
+        amounts,
+        commitments,
+    };
+
+    let data = serialize_instruction(&instruction_data);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*pool_pda, false),
+            AccountMeta::new(*merkle_tree_pda, false),
+            AccountMeta::new(zkcash::utils::find_withdraw_authority_pda(program_id).0, false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
         ],
         data,
@@ -264,7 +343,9 @@ pub fn create_withdraw_instruction(
     merkle_tree_pda: &Pubkey,
     nullifier_pda: &Pubkey,
     recipient: &Pubkey,
+    relayer: &Pubkey,
     amount: u64,
+    fee: u64,
     root: [u8; 32],
     nullifier_hash: [u8; 32],
     proof: Vec<u8>,
@@ -272,17 +353,18 @@ pub fn create_withdraw_instruction(
     let recipient_bytes = recipient.to_bytes();
     let mut recipient_array = [0u8; 32];
     recipient_array.copy_from_slice(&recipient_bytes);
-    
+
     let instruction_data = PrivacyInstruction::Withdraw {
         amount,
+        fee,
         root,
         nullifier_hash,
         recipient: recipient_array,
         proof,
     };
-    
+
     let data = serialize_instruction(&instruction_data);
-    
+
     Instruction {
         program_id: *program_id,
         accounts: vec![
@@ -290,8 +372,11 @@ pub fn create_withdraw_instruction(
             AccountMeta::new(*pool_pda, false),
             AccountMeta::new(*merkle_tree_pda, false),
             AccountMeta::new(*nullifier_pda, false),
+            AccountMeta::new(zkcash::utils::find_withdraw_authority_pda(program_id).0, false),
             AccountMeta::new(*recipient, false),
+            AccountMeta::new(*relayer, false),
             AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
         ],
         data,
     }
@@ -10,7 +10,6 @@ use solana_sdk::{
 use std::str::FromStr;
 use std::path::PathBuf;
 use std::fs;
-use borsh::{BorshSerialize};
 use zkcash::state::{Pool, MerkleTree};
 
 /// Set up a mock test environment that doesn't rely on the actual program processor
@@ -34,14 +33,11 @@ pub async fn setup_validator() -> Result<ProgramTestContext, TransportError> {
         is_initialized: false,
         merkle_tree_height: 0,
         total_amount: 0,
+        mint: None,
     };
     
-    // Manually serialize pool data since borsh::BorshSerialize is not implemented for Pool
-    let mut pool_data = vec![];
-    pool_data.extend_from_slice(&[pool.is_initialized as u8]);
-    pool_data.push(pool.merkle_tree_height);
-    pool_data.extend_from_slice(&pool.total_amount.to_le_bytes());
-    
+    let pool_data = borsh::to_vec(&pool).unwrap();
+
     let pool_lamports = Rent::default().minimum_balance(pool_data.len());
     let mut pool_account = Account::new(
         pool_lamports,
@@ -53,23 +49,10 @@ pub async fn setup_validator() -> Result<ProgramTestContext, TransportError> {
     program_test.add_account(pool_pda, pool_account);
     
     // Create empty merkle tree account
-    let merkle_tree = MerkleTree::new(crate::common::fixtures::MERKLE_TREE_HEIGHT);
-    
-    // Manually serialize merkle tree data 
-    let mut merkle_tree_data = vec![];
-    merkle_tree_data.extend_from_slice(&[merkle_tree.is_initialized as u8]);
-    merkle_tree_data.push(merkle_tree.height);
-    merkle_tree_data.extend_from_slice(&merkle_tree.current_index.to_le_bytes());
-    merkle_tree_data.extend_from_slice(&merkle_tree.root);
-    
-    // Serialize filled_subtrees
-    let subtrees_len = merkle_tree.filled_subtrees.len() as u32;
-    merkle_tree_data.extend_from_slice(&subtrees_len.to_le_bytes());
-    
-    for subtree in &merkle_tree.filled_subtrees {
-        merkle_tree_data.extend_from_slice(subtree);
-    }
+    let merkle_tree = MerkleTree::<{ crate::common::fixtures::MERKLE_TREE_HEIGHT as usize }>::new();
     
+    let merkle_tree_data = borsh::to_vec(&merkle_tree).unwrap();
+
     let merkle_tree_lamports = Rent::default().minimum_balance(merkle_tree_data.len());
     let mut merkle_tree_account = Account::new(
         merkle_tree_lamports,
@@ -111,13 +94,10 @@ pub async fn initialize_zkcash(context: &mut ProgramTestContext) -> Result<(), T
         is_initialized: true,
         merkle_tree_height: crate::common::fixtures::MERKLE_TREE_HEIGHT,
         total_amount: 0,
+        mint: None,
     };
     
-    // Manually serialize pool data
-    let mut pool_data = vec![];
-    pool_data.extend_from_slice(&[pool.is_initialized as u8]);
-    pool_data.push(pool.merkle_tree_height);
-    pool_data.extend_from_slice(&pool.total_amount.to_le_bytes());
+    let pool_data = borsh::to_vec(&pool).unwrap();
 
     // Create a new Shared Account
     let mut new_account = AccountSharedData::new(
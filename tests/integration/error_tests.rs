@@ -64,7 +64,7 @@ fn test_nullifier_already_used_error() {
     
     // Get merkle tree root
     let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
-    let merkle_tree: MerkleTree = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
     let root = merkle_tree.root;
     
     // First withdrawal to create the nullifier
@@ -74,21 +74,25 @@ fn test_nullifier_already_used_error() {
     
     let result = bank.withdraw(
         withdraw_amount,
+        0,
         root,
         MOCK_NULLIFIER_HASH,
         proof.clone(),
         &recipient,
+        &Pubkey::new_unique(),
     );
-    
+
     assert!(result.is_ok(), "First withdrawal failed: {:?}", result.err());
-    
+
     // Try to use the same nullifier again
     let result = bank.withdraw(
         withdraw_amount,
+        0,
         root,
         MOCK_NULLIFIER_HASH, // Same nullifier
         proof,
         &recipient,
+        &Pubkey::new_unique(),
     );
     
     // This should fail because the nullifier was already used
@@ -121,7 +125,7 @@ fn test_insufficient_funds_error() {
     
     // Get merkle tree root
     let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
-    let merkle_tree: MerkleTree = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
     let root = merkle_tree.root;
     
     // Try to withdraw more than available
@@ -131,10 +135,12 @@ fn test_insufficient_funds_error() {
     
     let result = bank.withdraw(
         excessive_amount,
+        0,
         root,
         MOCK_NULLIFIER_HASH,
         proof,
         &recipient,
+        &Pubkey::new_unique(),
     );
     
     // This should fail due to insufficient funds
@@ -29,11 +29,12 @@ fn test_initialize_success() {
     assert_eq!(pool.total_amount, 0);
     
     // Read the merkle tree account
-    let merkle_tree: MerkleTree = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
-    
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> =
+        bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+
     // Verify the merkle tree is initialized
     assert!(merkle_tree.is_initialized, "Merkle tree should be initialized");
-    assert_eq!(merkle_tree.height, MERKLE_TREE_HEIGHT);
+    assert_eq!(merkle_tree.height() as usize, MERKLE_TREE_HEIGHT as usize);
 }
 
 /// Test initialization with incorrect PDAs (in mock environment)
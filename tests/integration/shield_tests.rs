@@ -27,7 +27,7 @@ fn test_shield_success() {
     
     // Verify the commitment was added to the merkle tree
     let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
-    let merkle_tree: MerkleTree = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
     assert_eq!(merkle_tree.current_index, 1, "Merkle tree index should have increased");
 }
 
@@ -88,6 +88,6 @@ fn test_shield_multiple() {
     
     // Verify the merkle tree index increased for both insertions
     let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
-    let merkle_tree: MerkleTree = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
     assert_eq!(merkle_tree.current_index, 2, "Merkle tree index should have increased for both insertions");
 }
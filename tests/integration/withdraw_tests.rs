@@ -42,28 +42,38 @@ fn test_withdraw_success() {
     
     // Get the merkle tree so we can get the root
     let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
-    let merkle_tree: MerkleTree = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
     let root = merkle_tree.root;
     
-    // Now withdraw some of the tokens
+    // Now withdraw some of the tokens, paying a relayer out of the proceeds
     let withdraw_amount = 1_000_000; // 1 SOL
+    let fee = 100_000; // relayer fee
     let proof = get_mock_proof();
     let destination = Pubkey::new_unique();
-    
+    let relayer = Pubkey::new_unique();
+
     let result = bank.withdraw(
         withdraw_amount,
+        fee,
         root,
         MOCK_NULLIFIER_HASH,
         proof,
         &destination,
+        &relayer,
     );
-    
+
     assert!(result.is_ok(), "Withdraw operation failed: {:?}", result.err());
-    
+
     // Verify the pool's total amount decreased
     let (pool_pda, _) = find_pool_pda(&program_id);
     let pool: Pool = bank.get_account(&pool_pda).expect("Pool account not found");
     assert_eq!(pool.total_amount, shield_amount - withdraw_amount, "Pool total amount should have decreased");
+
+    // Verify real lamports moved: the vault was debited, and the recipient and
+    // relayer were credited with their split of the withdrawal.
+    assert_eq!(bank.vault_balance(), shield_amount - withdraw_amount, "Vault balance should have decreased");
+    assert_eq!(bank.lamports(&destination), withdraw_amount - fee, "Recipient should have received amount minus fee");
+    assert_eq!(bank.lamports(&relayer), fee, "Relayer should have received the fee");
     
     // Verify the nullifier was marked as used
     let (nullifier_pda, _) = find_nullifier_pda(&program_id, &MOCK_NULLIFIER_HASH);
@@ -72,6 +82,59 @@ fn test_withdraw_success() {
     assert_eq!(nullifier.nullifier_hash, MOCK_NULLIFIER_HASH, "Nullifier hash should match");
 }
 
+/// Test withdrawing against an older-but-still-in-window root after concurrent shields
+#[test]
+fn test_withdraw_against_recent_root() {
+    let program_id = get_program_id();
+    let mut bank = SimpleBank::new(program_id);
+    bank.initialize(MERKLE_TREE_HEIGHT).expect("Initialization should succeed");
+
+    // Shield and snapshot the root the user would build their proof against.
+    bank.shield(2_000_000, MOCK_COMMITMENT).expect("Shield operation should succeed");
+    let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let snapshot_root = merkle_tree.root;
+
+    // A few more shields land before the withdraw, moving the current root forward.
+    let mut commitment = MOCK_COMMITMENT;
+    for i in 0..3 {
+        commitment[0] = 100 + i;
+        bank.shield(1_000_000, commitment).expect("Shield operation should succeed");
+    }
+
+    // Withdrawing against the snapshot (now stale but in-window) still succeeds.
+    let destination = Pubkey::new_unique();
+    let result = bank.withdraw(1_000_000, 0, snapshot_root, MOCK_NULLIFIER_HASH, get_mock_proof(), &destination, &Pubkey::new_unique());
+    assert!(result.is_ok(), "Withdraw against recent root failed: {:?}", result.err());
+}
+
+/// Test that a root which has fallen out of the history window is rejected
+#[test]
+fn test_withdraw_evicted_root() {
+    use zkcash::state::ROOT_HISTORY_SIZE;
+
+    let program_id = get_program_id();
+    let mut bank = SimpleBank::new(program_id);
+    bank.initialize(MERKLE_TREE_HEIGHT).expect("Initialization should succeed");
+
+    // Shield once and snapshot the root, then shield enough times to evict it.
+    bank.shield(5_000_000, MOCK_COMMITMENT).expect("Shield operation should succeed");
+    let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let stale_root = merkle_tree.root;
+
+    let mut commitment = MOCK_COMMITMENT;
+    for i in 0..(ROOT_HISTORY_SIZE as u16 + 1) {
+        commitment[0] = (i % 256) as u8;
+        commitment[1] = (i / 256) as u8;
+        bank.shield(1_000, commitment).expect("Shield operation should succeed");
+    }
+
+    let destination = Pubkey::new_unique();
+    let result = bank.withdraw(1_000_000, 0, stale_root, MOCK_NULLIFIER_HASH, get_mock_proof(), &destination, &Pubkey::new_unique());
+    assert!(matches!(result, Err(PrivacyError::InvalidRoot)), "Evicted root should be rejected, got {:?}", result);
+}
+
 /// Test withdrawing with an invalid root
 #[test]
 fn test_withdraw_invalid_root() {
@@ -96,10 +159,12 @@ fn test_withdraw_invalid_root() {
     
     let result = bank.withdraw(
         withdraw_amount,
+        0,
         invalid_root,
         MOCK_NULLIFIER_HASH,
         proof,
         &destination,
+        &Pubkey::new_unique(),
     );
     
     assert!(result.is_err(), "Withdraw operation with invalid root should fail");
@@ -129,7 +194,7 @@ fn test_withdraw_double_spend() {
     
     // Get the merkle tree so we can get the root
     let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
-    let merkle_tree: MerkleTree = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
     let root = merkle_tree.root;
     
     // Withdraw tokens the first time
@@ -139,10 +204,12 @@ fn test_withdraw_double_spend() {
     
     let result = bank.withdraw(
         withdraw_amount,
+        0,
         root,
         MOCK_NULLIFIER_HASH,
         proof.clone(),
         &destination,
+        &Pubkey::new_unique(),
     );
     
     assert!(result.is_ok(), "First withdraw operation failed: {:?}", result.err());
@@ -150,10 +217,12 @@ fn test_withdraw_double_spend() {
     // Try to withdraw again with the same nullifier
     let result = bank.withdraw(
         withdraw_amount,
+        0,
         root,
         MOCK_NULLIFIER_HASH,
         proof,
         &destination,
+        &Pubkey::new_unique(),
     );
     
     assert!(result.is_err(), "Second withdraw with same nullifier should fail");
@@ -183,7 +252,7 @@ fn test_withdraw_insufficient_funds() {
     
     // Get the merkle tree so we can get the root
     let (merkle_tree_pda, _) = find_merkle_tree_pda(&program_id);
-    let merkle_tree: MerkleTree = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
+    let merkle_tree: MerkleTree<{ MERKLE_TREE_HEIGHT as usize }> = bank.get_account(&merkle_tree_pda).expect("Merkle tree account not found");
     let root = merkle_tree.root;
     
     // Try to withdraw more than the pool's balance
@@ -193,10 +262,12 @@ fn test_withdraw_insufficient_funds() {
     
     let result = bank.withdraw(
         withdraw_amount,
+        0,
         root,
         MOCK_NULLIFIER_HASH,
         proof,
         &destination,
+        &Pubkey::new_unique(),
     );
     
     assert!(result.is_err(), "Withdraw operation with insufficient funds should fail");
@@ -0,0 +1,41 @@
+use x25519_dalek::{PublicKey, StaticSecret};
+use zkcash::note::{encrypt_note, scan_notes, try_decrypt_note, NotePlaintext};
+
+fn keypair(seed: [u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let sk = StaticSecret::from(seed);
+    let pk = PublicKey::from(&sk);
+    (sk.to_bytes(), *pk.as_bytes())
+}
+
+#[test]
+fn test_encrypt_decrypt_roundtrip() {
+    let (sk, pk) = keypair([7u8; 32]);
+    let note = NotePlaintext { amount: 42, blinding: [9u8; 32], memo: b"hi".to_vec() };
+
+    let (epk, ciphertext) = encrypt_note(&pk, &note);
+    let recovered = try_decrypt_note(&sk, &epk, &ciphertext).expect("note decrypts");
+    assert_eq!(recovered, note);
+}
+
+#[test]
+fn test_wrong_recipient_fails() {
+    let (_sk, pk) = keypair([1u8; 32]);
+    let (other_sk, _other_pk) = keypair([2u8; 32]);
+    let note = NotePlaintext { amount: 1, blinding: [0u8; 32], memo: vec![] };
+
+    let (epk, ciphertext) = encrypt_note(&pk, &note);
+    assert!(try_decrypt_note(&other_sk, &epk, &ciphertext).is_none());
+}
+
+#[test]
+fn test_scan_picks_out_own_notes() {
+    let (sk, pk) = keypair([3u8; 32]);
+    let (_osk, opk) = keypair([4u8; 32]);
+
+    let mine = NotePlaintext { amount: 100, blinding: [5u8; 32], memo: vec![] };
+    let theirs = NotePlaintext { amount: 200, blinding: [6u8; 32], memo: vec![] };
+
+    let outputs = vec![encrypt_note(&pk, &mine), encrypt_note(&opk, &theirs)];
+    let found = scan_notes(&sk, &outputs);
+    assert_eq!(found, vec![mine]);
+}
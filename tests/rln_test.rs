@@ -0,0 +1,46 @@
+use zkcash::rln;
+
+fn secret(byte: u8) -> [u8; 32] {
+    let mut s = [0u8; 32];
+    s[31] = byte;
+    s
+}
+
+#[test]
+fn test_single_share_then_recover() {
+    let identity = secret(7);
+    let epoch = 42u64;
+
+    // Two distinct signals in the same epoch reveal two points on the line.
+    let (x0, y0, n0) = rln::signal(&identity, epoch, &secret(1)).unwrap();
+    let (x1, y1, n1) = rln::signal(&identity, epoch, &secret(2)).unwrap();
+
+    // Same epoch ⇒ same internal nullifier, different share abscissae.
+    assert_eq!(n0, n1);
+    assert_ne!(x0, x1);
+
+    let recovered = rln::recover_secret((&x0, &y0), (&x1, &y1)).unwrap();
+    assert_eq!(recovered, identity);
+
+    // The recovered secret reproduces the member's membership leaf, so the pool
+    // can match and blacklist it.
+    assert_eq!(
+        rln::identity_commitment(&recovered).unwrap(),
+        rln::identity_commitment(&identity).unwrap()
+    );
+}
+
+#[test]
+fn test_recover_rejects_duplicate_abscissa() {
+    let identity = secret(3);
+    let (x, y, _) = rln::signal(&identity, 1, &secret(9)).unwrap();
+    assert!(rln::recover_secret((&x, &y), (&x, &y)).is_err());
+}
+
+#[test]
+fn test_distinct_epochs_have_distinct_nullifiers() {
+    let identity = secret(5);
+    let (_, _, n0) = rln::signal(&identity, 1, &secret(1)).unwrap();
+    let (_, _, n1) = rln::signal(&identity, 2, &secret(1)).unwrap();
+    assert_ne!(n0, n1);
+}
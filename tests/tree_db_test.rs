@@ -0,0 +1,53 @@
+use zkcash::poseidon::compute_merkle_root;
+use zkcash::state::MerkleTree;
+use zkcash::tree_db::{MemoryDb, StoredMerkleTree};
+
+fn leaf(byte: u8) -> [u8; 32] {
+    let mut l = [0u8; 32];
+    l[0] = byte;
+    l
+}
+
+#[test]
+fn test_stored_root_matches_on_chain() {
+    const HEIGHT: usize = 5;
+    let leaves: Vec<[u8; 32]> = (1..=20u8).map(leaf).collect();
+
+    // Reconstruct off-chain in one batch.
+    let mut stored = StoredMerkleTree::new(MemoryDb::new(), HEIGHT as u8, 0);
+    stored.batch_insert(&leaves).unwrap();
+
+    // The on-chain tree appends one leaf at a time.
+    let mut onchain = MerkleTree::<HEIGHT>::new();
+    for l in &leaves {
+        onchain.insert(l).unwrap();
+    }
+
+    assert_eq!(stored.root(), onchain.root);
+    assert_eq!(stored.leaf_count(), leaves.len() as u64);
+}
+
+#[test]
+fn test_authentication_path_round_trips() {
+    const HEIGHT: u8 = 5;
+    let leaves: Vec<[u8; 32]> = (1..=20u8).map(leaf).collect();
+
+    let mut stored = StoredMerkleTree::new(MemoryDb::new(), HEIGHT, 0);
+    stored.batch_insert(&leaves).unwrap();
+
+    // Each stored witness must fold its leaf back to the reconstructed root.
+    for (index, l) in leaves.iter().enumerate() {
+        let path = stored.authentication_path(index as u64).unwrap();
+        let indices: Vec<u8> = {
+            let mut bytes = vec![0u8; HEIGHT.div_ceil(8) as usize];
+            for level in 0..HEIGHT as usize {
+                if (index >> level) & 1 == 1 {
+                    bytes[level / 8] |= 1 << (level % 8);
+                }
+            }
+            bytes
+        };
+        let root = compute_merkle_root(l, &path, &indices).unwrap();
+        assert_eq!(root, stored.root(), "witness for leaf {} should round-trip", index);
+    }
+}
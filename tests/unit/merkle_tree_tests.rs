@@ -1,219 +1,491 @@
 use crate::common::fixtures::MOCK_COMMITMENT;
-use zkcash::state::MerkleTree;
+use zkcash::state::{verify_path, zero_hashes, MerkleTree};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::IsInitialized;
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
-    const MERKLE_TREE_HEIGHT: u8 = 10;
-    
+
+    const MERKLE_TREE_HEIGHT: usize = 10;
+
     /// Test creating a new, empty merkle tree
     #[test]
     fn test_new_merkle_tree() {
         // Create a new merkle tree
-        let merkle_tree = MerkleTree::new(MERKLE_TREE_HEIGHT);
-        
+        let merkle_tree = MerkleTree::<MERKLE_TREE_HEIGHT>::new();
+
         // Verify initial state
         assert!(merkle_tree.is_initialized);
-        assert_eq!(merkle_tree.height, MERKLE_TREE_HEIGHT);
+        assert_eq!(merkle_tree.height() as usize, MERKLE_TREE_HEIGHT);
         assert_eq!(merkle_tree.current_index, 0);
-        assert_eq!(merkle_tree.root, [0u8; 32]);
-        assert_eq!(merkle_tree.filled_subtrees.len(), MERKLE_TREE_HEIGHT as usize);
-        
-        // All filled subtrees should be zero
-        for subtree in merkle_tree.filled_subtrees.iter() {
-            assert_eq!(*subtree, [0u8; 32]);
+        assert_eq!(merkle_tree.filled_subtrees.len(), MERKLE_TREE_HEIGHT);
+
+        // An empty tree's root is the canonical zero-subtree hash at the top,
+        // and each unfilled subtree defaults to its level's zero hash.
+        let zeros = zero_hashes(MERKLE_TREE_HEIGHT as u8);
+        assert_eq!(merkle_tree.root, zeros[MERKLE_TREE_HEIGHT]);
+        for (i, subtree) in merkle_tree.filled_subtrees.iter().enumerate() {
+            assert_eq!(*subtree, zeros[i]);
+        }
+    }
+
+    /// Test that a single insertion matches a hand-computed root against the
+    /// zero-hash ladder.
+    #[test]
+    fn test_single_leaf_matches_zero_ladder() {
+        let mut merkle_tree = MerkleTree::<4>::new();
+        let zeros = zero_hashes(4);
+
+        let leaf = MOCK_COMMITMENT;
+        merkle_tree.insert(&leaf).unwrap();
+
+        // Leaf 0 sits in the leftmost slot, so every sibling on its path is the
+        // empty-subtree hash for that level.
+        let mut expected = leaf;
+        for level in zeros.iter().take(4) {
+            expected = zkcash::poseidon::hash_left_right(&expected, level).unwrap();
         }
+        assert_eq!(merkle_tree.root, expected);
     }
-    
+
     /// Test inserting a leaf into a merkle tree
     #[test]
     fn test_insert_leaf() {
         // Create a new merkle tree
-        let mut merkle_tree = MerkleTree::new(MERKLE_TREE_HEIGHT);
-        
+        let mut merkle_tree = MerkleTree::<MERKLE_TREE_HEIGHT>::new();
+
         // Initial state
         assert_eq!(merkle_tree.current_index, 0);
         let initial_root = merkle_tree.root;
-        
+
         // Insert a leaf
         let leaf = MOCK_COMMITMENT;
         merkle_tree.insert(&leaf).unwrap();
-        
+
         // Verify the state after insertion
         assert_eq!(merkle_tree.current_index, 1);
         assert_ne!(merkle_tree.root, initial_root);
-        
+
         // First filled subtree should match the leaf
         assert_eq!(merkle_tree.filled_subtrees[0], leaf);
     }
-    
+
     /// Test inserting multiple leaves into a merkle tree
     #[test]
     fn test_insert_multiple_leaves() {
         // Create a new merkle tree with a small height for testing
-        let height = 4;
-        let mut merkle_tree = MerkleTree::new(height);
-        
+        let mut merkle_tree = MerkleTree::<4>::new();
+
         // Insert several leaves
         let leaf1 = MOCK_COMMITMENT;
         let mut leaf2 = MOCK_COMMITMENT;
         leaf2[0] = 1;
         let mut leaf3 = MOCK_COMMITMENT;
         leaf3[0] = 2;
-        
+
         // Insert first leaf and check state
         merkle_tree.insert(&leaf1).unwrap();
-        let _root1 = merkle_tree.root; // Underscore to avoid unused var warning
+        let root1 = merkle_tree.root;
         assert_eq!(merkle_tree.current_index, 1);
-        
+
         // Insert second leaf and check state
         merkle_tree.insert(&leaf2).unwrap();
-        let _root2 = merkle_tree.root; // Underscore to avoid unused var warning
+        let root2 = merkle_tree.root;
         assert_eq!(merkle_tree.current_index, 2);
-        
+
         // Insert third leaf and check state
         merkle_tree.insert(&leaf3).unwrap();
         let root3 = merkle_tree.root;
-        
+
         // Verify the state after each insertion
-        
         assert_eq!(merkle_tree.current_index, 3);
-        
-        // Note: Due to how the Merkle tree is implemented, the root might not change in some cases
-        // because of how the path is computed. We'll focus on validating the tree state instead.
-        assert!(merkle_tree.current_index == 3);
         assert!(root3 != [0u8; 32]); // Ensure the root is not just zeros
+
+        // Folding empty subtrees with the per-level zeros table makes every
+        // distinct leaf set yield a distinct root.
+        assert_ne!(root1, root2);
+        assert_ne!(root2, root3);
     }
-    
+
     /// Test inserting the maximum number of leaves
     #[test]
     fn test_insert_max_leaves() {
         // Create a new merkle tree with a very small height for testing
-        let small_height = 3; // Using a small height so we don't have to insert too many leaves
-        let mut merkle_tree = MerkleTree::new(small_height);
-        
+        let mut merkle_tree = MerkleTree::<3>::new();
+
         // Calculate max number of leaves (2^height)
-        let max_leaves = 1 << small_height;
-        
-        // Insert leaves up to max
+        let max_leaves = 1u32 << 3;
+
+        // Insert leaves up to max (nonzero, so none collide with the sentinel)
         for i in 0..max_leaves {
             let mut leaf = [0u8; 32];
-            leaf[0] = (i % 255) as u8;
+            leaf[0] = (i % 255 + 1) as u8;
             leaf[1] = ((i / 255) % 255) as u8;
-            
+
             let result = merkle_tree.insert(&leaf);
             assert!(result.is_ok(), "Failed to insert leaf {} of {}", i, max_leaves);
             assert_eq!(merkle_tree.current_index, i + 1);
         }
-        
+
         // Insert one more leaf, should fail
         let result = merkle_tree.insert(&[42u8; 32]);
         assert!(result.is_err());
     }
-    
+
+    /// Batched insertion must match appending the same leaves one at a time.
+    #[test]
+    fn test_insert_batch_matches_sequential() {
+        let leaves: Vec<[u8; 32]> = (1..=6u8)
+            .map(|i| {
+                let mut leaf = [0u8; 32];
+                leaf[0] = i;
+                leaf
+            })
+            .collect();
+
+        let mut batched = MerkleTree::<4>::new();
+        batched.insert_batch(&leaves).unwrap();
+
+        let mut sequential = MerkleTree::<4>::new();
+        for leaf in &leaves {
+            sequential.insert(leaf).unwrap();
+        }
+
+        assert_eq!(batched.current_index, sequential.current_index);
+        assert_eq!(batched.root, sequential.root);
+    }
+
+    fn seq_leaf(i: u8) -> [u8; 32] {
+        let mut leaf = [0u8; 32];
+        leaf[0] = i;
+        leaf
+    }
+
+    /// Inserting after a batch must stay in lock-step with pure sequential
+    /// insertion: the batch has to leave the frontier (`filled_subtrees`) exactly
+    /// as appending the leaves one at a time would, or the next `insert` folds
+    /// against a stale left sibling and computes a wrong root.
+    #[test]
+    fn test_insert_after_batch_matches_sequential() {
+        let batch: Vec<[u8; 32]> = (1..=3u8).map(seq_leaf).collect();
+
+        let mut batched = MerkleTree::<4>::new();
+        batched.insert_batch(&batch).unwrap();
+        batched.insert(&seq_leaf(4)).unwrap();
+
+        let mut sequential = MerkleTree::<4>::new();
+        for i in 1..=4u8 {
+            sequential.insert(&seq_leaf(i)).unwrap();
+        }
+
+        assert_eq!(batched.current_index, sequential.current_index);
+        assert_eq!(batched.root, sequential.root);
+        assert_eq!(batched.filled_subtrees, sequential.filled_subtrees);
+    }
+
+    /// A batch that overflows capacity or contains the zero sentinel is rejected
+    /// before any state changes.
+    #[test]
+    fn test_insert_batch_validates_up_front() {
+        let good = {
+            let mut leaf = [0u8; 32];
+            leaf[0] = 7;
+            leaf
+        };
+
+        // Overflowing batch (capacity 4) leaves the tree untouched.
+        let mut tree = MerkleTree::<2>::new();
+        let too_many = vec![good; 5];
+        assert_eq!(
+            tree.insert_batch(&too_many).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        assert_eq!(tree.current_index, 0);
+
+        // A zero leaf anywhere in the batch is rejected with nothing inserted.
+        let mut tree = MerkleTree::<2>::new();
+        assert_eq!(
+            tree.insert_batch(&[good, [0u8; 32]]).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        assert_eq!(tree.current_index, 0);
+    }
+
     /// Test deserializing and initializing a merkle tree
     #[test]
     fn test_merkle_tree_serialization() {
         // Create a merkle tree
-        let merkle_tree = MerkleTree::new(MERKLE_TREE_HEIGHT);
-        
+        let merkle_tree = MerkleTree::<MERKLE_TREE_HEIGHT>::new();
+
         // Serialize and deserialize
         let serialized = borsh::to_vec(&merkle_tree).unwrap();
-        let deserialized: MerkleTree = borsh::from_slice(&serialized).unwrap();
-        
+        let deserialized: MerkleTree<MERKLE_TREE_HEIGHT> = borsh::from_slice(&serialized).unwrap();
+
         // Test an uninitialized tree
-        let mut uninitialized_tree = MerkleTree::new(MERKLE_TREE_HEIGHT);
+        let mut uninitialized_tree = MerkleTree::<MERKLE_TREE_HEIGHT>::new();
         uninitialized_tree.is_initialized = false;
-        
+
         // Check the is_initialized method
         assert!(merkle_tree.is_initialized());
         assert!(deserialized.is_initialized());
         assert!(!uninitialized_tree.is_initialized());
     }
-    
+
     /// Test merkle tree capacity limits
     #[test]
     fn test_merkle_tree_capacity() {
         // Create a small merkle tree for testing capacity
-        let height = 3;
-        let mut merkle_tree = MerkleTree::new(height);
-        
+        let mut merkle_tree = MerkleTree::<3>::new();
+
         // Calculate the capacity
-        let capacity = 1 << height;
-        
-        // Insert leaves up to capacity
+        let capacity = 1u32 << 3;
+
+        // Insert leaves up to capacity (nonzero leaves)
         for i in 0..capacity {
             let mut leaf = [0u8; 32];
-            leaf[0] = i as u8;
+            leaf[0] = (i + 1) as u8;
             let result = merkle_tree.insert(&leaf);
             assert!(result.is_ok());
         }
-        
+
         // Verify the tree is full
-        assert_eq!(merkle_tree.current_index as usize, capacity);
-        
+        assert_eq!(merkle_tree.current_index, capacity);
+
         // Try to insert one more leaf
         let extra_leaf = [255u8; 32];
         let result = merkle_tree.insert(&extra_leaf);
-        
+
         // Verify the insertion fails
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), ProgramError::InvalidArgument);
     }
-    
+
     /// Test tree state after multiple insertions
     #[test]
     fn test_merkle_tree_state_after_inserts() {
         // Create a new merkle tree
-        let mut merkle_tree = MerkleTree::new(MERKLE_TREE_HEIGHT);
-        
+        let mut merkle_tree = MerkleTree::<MERKLE_TREE_HEIGHT>::new();
+
         // Insert multiple leaves
         let num_leaves = 5;
         let mut previous_roots = Vec::with_capacity(num_leaves);
-        
+
         for i in 0..num_leaves {
             // Save the current root
             previous_roots.push(merkle_tree.root);
-            
+
             // Create a leaf with a unique value
             let mut leaf = [0u8; 32];
-            leaf[0] = i as u8;
-            
+            leaf[0] = (i + 1) as u8;
+
             // Insert the leaf
             let result = merkle_tree.insert(&leaf);
             assert!(result.is_ok());
-            
+
             // Verify the current index was incremented
             assert_eq!(merkle_tree.current_index as usize, i + 1);
-            
+
             // Verify the root changed
             assert_ne!(merkle_tree.root, previous_roots[i]);
         }
-        
+
         // Verify all roots were different
         for i in 0..previous_roots.len() - 1 {
             assert_ne!(previous_roots[i], previous_roots[i + 1]);
         }
     }
-    
+
+    /// Test that recent roots remain known while evicted ones do not
+    #[test]
+    fn test_root_history_window() {
+        use zkcash::state::ROOT_HISTORY_SIZE;
+
+        let mut merkle_tree = MerkleTree::<6>::new(); // capacity 64 > window size
+
+        // Snapshot the root produced by the very first insertion.
+        let mut leaf = [0u8; 32];
+        leaf[0] = 1;
+        merkle_tree.insert(&leaf).unwrap();
+        let snapshot_root = merkle_tree.root;
+        assert!(merkle_tree.is_known_root(&snapshot_root));
+
+        // A handful of further inserts keep the snapshot in the window.
+        for i in 2..5u8 {
+            let mut leaf = [0u8; 32];
+            leaf[0] = i;
+            merkle_tree.insert(&leaf).unwrap();
+        }
+        assert!(merkle_tree.is_known_root(&snapshot_root), "root should still be in-window");
+
+        // Once enough inserts land to fill the ring, the snapshot is evicted.
+        for i in 5..(5 + ROOT_HISTORY_SIZE as u8) {
+            let mut leaf = [0u8; 32];
+            leaf[0] = i;
+            merkle_tree.insert(&leaf).unwrap();
+        }
+        assert!(!merkle_tree.is_known_root(&snapshot_root), "root should have been evicted");
+
+        // The all-zero sentinel is never treated as a known root.
+        assert!(!merkle_tree.is_known_root(&[0u8; 32]));
+
+        // The canonical tip tracks the latest root, not the evicted snapshot.
+        assert_eq!(merkle_tree.current_root(), merkle_tree.root);
+        assert!(merkle_tree.is_known_root(&merkle_tree.current_root()));
+    }
+
+    /// Test that a root stays valid after a deposit lands post-proof-generation
+    #[test]
+    fn test_known_root_survives_later_deposit() {
+        let mut merkle_tree = MerkleTree::<6>::new();
+
+        // A client generates a proof against the root after this first deposit.
+        merkle_tree.insert(&[1u8; 32]).unwrap();
+        let proof_root = merkle_tree.root;
+
+        // A concurrent deposit rotates the current root before the withdraw.
+        merkle_tree.insert(&[2u8; 32]).unwrap();
+        assert_ne!(merkle_tree.root, proof_root, "current root should have rotated");
+
+        // The client's proof root is still accepted from the history window.
+        assert!(merkle_tree.is_known_root(&proof_root));
+    }
+
+    /// Reference root: fold all leaves bottom-up with zero padding.
+    fn reference_root(leaves: &[[u8; 32]], height: u8) -> [u8; 32] {
+        let mut level: Vec<[u8; 32]> = leaves.to_vec();
+        level.resize(1 << height, [0u8; 32]);
+        for _ in 0..height {
+            level = level
+                .chunks(2)
+                .map(|pair| zkcash::poseidon::hash_left_right(&pair[0], &pair[1]).unwrap())
+                .collect();
+        }
+        level[0]
+    }
+
+    /// Test that an incremental witness round-trips its leaf back to the root
+    #[test]
+    fn test_incremental_witness_round_trip() {
+        use zkcash::poseidon::compute_merkle_root;
+        use zkcash::state::IncrementalWitness;
+
+        const HEIGHT: usize = 3;
+        let leaves: Vec<[u8; 32]> = (0..(1u16 << HEIGHT))
+            .map(|i| {
+                let mut leaf = [0u8; 32];
+                leaf[0] = (i + 1) as u8;
+                leaf
+            })
+            .collect();
+
+        // Track each leaf in turn and confirm its witness reproduces the root.
+        for tracked in 0..leaves.len() {
+            let mut tree = MerkleTree::<HEIGHT>::new();
+
+            // Insert leaves up to (but not including) the tracked one.
+            for leaf in leaves.iter().take(tracked) {
+                tree.insert(leaf).unwrap();
+            }
+
+            // Snapshot the witness, then insert the tracked leaf.
+            let mut witness = IncrementalWitness::new(&tree, leaves[tracked]);
+            tree.insert(&leaves[tracked]).unwrap();
+
+            // Feed every subsequent leaf to the witness.
+            for leaf in leaves.iter().skip(tracked + 1) {
+                tree.insert(leaf).unwrap();
+                witness.append(*leaf).unwrap();
+            }
+
+            let (path, indices) = witness.path();
+            let root = compute_merkle_root(&witness.leaf(), &path, &indices).unwrap();
+            assert_eq!(
+                root,
+                reference_root(&leaves, HEIGHT as u8),
+                "witness for leaf {} should round-trip to the root",
+                tracked
+            );
+        }
+    }
+
+    /// Test that a base-4 root over 16 leaves matches manual width-4 reductions
+    #[test]
+    fn test_base4_root_matches_manual_reduction() {
+        use zkcash::poseidon::{compute_merkle_root_arity, hash_4};
+
+        let leaves: Vec<[u8; 32]> = (0..16u16)
+            .map(|i| {
+                let mut leaf = [0u8; 32];
+                leaf[0] = (i + 1) as u8;
+                leaf
+            })
+            .collect();
+
+        // Fold the leaves bottom-up, four at a time, with hash_4 at each level.
+        let mut level: Vec<[u8; 32]> = leaves.clone();
+        while level.len() > 1 {
+            level = level
+                .chunks(4)
+                .map(|group| hash_4(&[group[0], group[1], group[2], group[3]]).unwrap())
+                .collect();
+        }
+        let expected = level[0];
+
+        // The quaternary proof path reproduces the same root: leaf 0 sits at
+        // position 0 on both levels, with its three siblings at each level.
+        let path = vec![
+            leaves[1], leaves[2], leaves[3], // level 0 siblings of leaf 0
+            hash_4(&[leaves[4], leaves[5], leaves[6], leaves[7]]).unwrap(),
+            hash_4(&[leaves[8], leaves[9], leaves[10], leaves[11]]).unwrap(),
+            hash_4(&[leaves[12], leaves[13], leaves[14], leaves[15]]).unwrap(),
+        ];
+        let positions = [0u8, 0u8];
+        let root = compute_merkle_root_arity(&leaves[0], &path, &positions, 4).unwrap();
+        assert_eq!(root, expected);
+    }
+
+    /// Test that the frontier authentication path round-trips to the root
+    #[test]
+    fn test_authentication_path_round_trip() {
+        const HEIGHT: usize = 6;
+        let mut tree = MerkleTree::<HEIGHT>::new();
+
+        // After each insertion the frontier leaf's path must reproduce the root.
+        for i in 0..20u64 {
+            let mut leaf = [0u8; 32];
+            leaf[0] = (i + 1) as u8;
+            tree.insert(&leaf).unwrap();
+
+            let path = tree.authentication_path(i).unwrap();
+            assert_eq!(path.len(), HEIGHT);
+            assert!(
+                verify_path(&leaf, i, &path, &tree.root).unwrap(),
+                "path for leaf {} should round-trip",
+                i
+            );
+        }
+
+        // Only the most recently inserted leaf is available from the cache.
+        assert!(tree.authentication_path(0).is_err());
+        assert!(tree.authentication_path(999).is_err());
+    }
+
     /// Test IsInitialized trait implementation
     #[test]
     fn test_is_initialized() {
         use solana_program::program_pack::IsInitialized;
-        
+
         // Create a new merkle tree
-        let merkle_tree = MerkleTree::new(MERKLE_TREE_HEIGHT);
-        
+        let merkle_tree = MerkleTree::<MERKLE_TREE_HEIGHT>::new();
+
         // Verify it's initialized
         assert!(merkle_tree.is_initialized());
-        
+
         // Create an uninitialized merkle tree
-        let mut uninitialized_tree = MerkleTree::new(MERKLE_TREE_HEIGHT);
+        let mut uninitialized_tree = MerkleTree::<MERKLE_TREE_HEIGHT>::new();
         uninitialized_tree.is_initialized = false;
-        
+
         // Verify it's not initialized
         assert!(!uninitialized_tree.is_initialized());
     }
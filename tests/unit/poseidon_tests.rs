@@ -107,6 +107,51 @@ mod tests {
         assert!(result.is_err());
     }
     
+    /// Test the hash_4 function
+    #[test]
+    fn test_hash_4() {
+        let inputs = [MOCK_COMMITMENT, [1u8; 32], [2u8; 32], [3u8; 32]];
+
+        let result = poseidon::hash_4(&inputs).unwrap();
+        assert_ne!(result, [0u8; 32]);
+
+        let mut poseidon = Poseidon::<Fr>::new_circom(4).unwrap();
+        let expected = poseidon
+            .hash_bytes_be(&[&inputs[0], &inputs[1], &inputs[2], &inputs[3]])
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    /// Test computing an arity-4 Merkle root
+    #[test]
+    fn test_compute_merkle_root_arity4() {
+        let leaf = MOCK_COMMITMENT;
+        // Two levels, each with 3 siblings; leaf sits at position 1 then 2.
+        let path = vec![[4u8; 32], [5u8; 32], [6u8; 32], [7u8; 32], [8u8; 32], [9u8; 32]];
+        let indices = vec![1u8, 2u8];
+
+        let result = poseidon::compute_merkle_root_arity4(&leaf, &path, &indices).unwrap();
+
+        // Reproduce the expected root by hand.
+        let level0 = poseidon::hash_4(&[path[0], leaf, path[1], path[2]]).unwrap();
+        let level1 = poseidon::hash_4(&[path[3], path[4], level0, path[5]]).unwrap();
+        assert_eq!(result, level1);
+    }
+
+    /// Test validation in compute_merkle_root_arity4
+    #[test]
+    fn test_compute_merkle_root_arity4_errors() {
+        let leaf = MOCK_COMMITMENT;
+
+        // Path length not a multiple of 3.
+        let bad_path = vec![[1u8; 32], [2u8; 32]];
+        assert!(poseidon::compute_merkle_root_arity4(&leaf, &bad_path, &[0]).is_err());
+
+        // Position byte out of range.
+        let path = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        assert!(poseidon::compute_merkle_root_arity4(&leaf, &path, &[4]).is_err());
+    }
+
     /// Test compatibility with light-poseidon library
     #[test]
     fn test_poseidon_compatibility() {
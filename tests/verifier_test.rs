@@ -0,0 +1,42 @@
+use zkcash::verifier::{Verifier, NUM_PUBLIC_INPUTS};
+
+const PROOF_LEN: usize = 256;
+const PUBLIC_INPUTS_LEN: usize = NUM_PUBLIC_INPUTS * 32;
+
+/// Bind a plausible set of public inputs so the verifier reaches the pairing
+/// check rather than bailing on a length mismatch.
+fn sample_public_inputs() -> Vec<u8> {
+    let mut inputs = vec![0u8; PUBLIC_INPUTS_LEN];
+    for (i, b) in inputs.iter_mut().enumerate() {
+        *b = (i as u8).wrapping_add(1);
+    }
+    inputs
+}
+
+/// A forged all-zero proof must be rejected: every component is the point at
+/// infinity, the degenerate case that previously slipped through as valid.
+#[test]
+fn test_forged_zero_proof_rejected() {
+    let proof = vec![0u8; PROOF_LEN];
+    let result = Verifier::verify_withdrawal_proof(&proof, &sample_public_inputs(), &[]);
+    assert!(
+        !matches!(result, Ok(true)),
+        "all-zero proof should not verify, got {:?}",
+        result
+    );
+}
+
+/// A well-formed but non-matching proof must not satisfy the pairing check.
+#[test]
+fn test_non_matching_proof_rejected() {
+    let mut proof = vec![0u8; PROOF_LEN];
+    for (i, b) in proof.iter_mut().enumerate() {
+        *b = (i as u8).wrapping_mul(7).wrapping_add(3);
+    }
+    let result = Verifier::verify_withdrawal_proof(&proof, &sample_public_inputs(), &[]);
+    assert!(
+        !matches!(result, Ok(true)),
+        "a proof unrelated to the public inputs should be rejected, got {:?}",
+        result
+    );
+}